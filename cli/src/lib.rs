@@ -0,0 +1,27 @@
+//! # gents_cli
+//! Generate `Typescript` files from the `Rust` source files directly, without
+//! compiling the crate or running the tests. It is useful for the build
+//! systems like `bazel`.
+//!
+//! Every group in `gents.toml` is generated, see `gents::build` for how the
+//! sources are handled:
+//! ```toml
+//! src = "src"
+//!
+//! [[groups]]
+//! name = "frontend"
+//! out_dir = "generated"
+//! index_file = true
+//! ```
+
+use std::path::Path;
+
+use gents::build::BuildOutput;
+use gents::Config;
+
+/// Generate the files according to the config file. The paths in the config
+/// are relative to the directory of the config file.
+pub fn run(config_path: &Path) -> BuildOutput {
+    let config = Config::from_file(config_path);
+    gents::build::generate(&config, None)
+}
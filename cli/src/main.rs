@@ -0,0 +1,19 @@
+use std::path::PathBuf;
+
+// gents [--config <gents.toml>]
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let config = match args.as_slice() {
+        [] => PathBuf::from("gents.toml"),
+        [flag, path] if flag == "--config" => PathBuf::from(path),
+        _ => {
+            eprintln!("usage: gents [--config <gents.toml>]");
+            std::process::exit(1);
+        }
+    };
+    let output = gents_cli::run(&config);
+    output
+        .warnings
+        .iter()
+        .for_each(|w| eprintln!("gents: {}", w));
+}
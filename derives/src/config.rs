@@ -0,0 +1,78 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::container::RenameAll;
+
+/// The settings in `gents.toml` of the crate being compiled, which are needed
+/// when expanding the macros.
+#[derive(Clone)]
+pub struct DeriveConfig {
+    path: Option<PathBuf>,
+    pub rename_all: Option<RenameAll>,
+}
+
+impl DeriveConfig {
+    /// The errors are reported as the compile errors of the derived types.
+    ///
+    /// The config is read once per crate and shared by all the expansions.
+    /// The crates are told apart by their directories, since a proc-macro
+    /// server like rust-analyzer may expand the macros of many crates.
+    pub fn load() -> Result<Self, String> {
+        static CONFIGS: OnceLock<Mutex<HashMap<String, Result<DeriveConfig, String>>>> =
+            OnceLock::new();
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+        let mut configs = CONFIGS.get_or_init(Default::default).lock().unwrap();
+        configs
+            .entry(dir.clone())
+            .or_insert_with(|| DeriveConfig::read(&dir))
+            .clone()
+    }
+
+    fn read(dir: &str) -> Result<Self, String> {
+        let path = Some(PathBuf::from(dir).join("gents.toml")).filter(|p| p.exists());
+        let rename_all = match &path {
+            Some(p) => {
+                let content = std::fs::read_to_string(p)
+                    .map_err(|e| format!("failed to read {}: {}", p.display(), e))?;
+                let table = content
+                    .parse::<toml::Table>()
+                    .map_err(|e| format!("failed to parse {}: {}", p.display(), e))?;
+                match table.get("rename_all") {
+                    None => None,
+                    Some(v) => match v.as_str() {
+                        Some("camelCase") => Some(RenameAll::CamelCase),
+                        _ => {
+                            return Err(format!(
+                                "unexpected rename_all in {}: {}, expected \"camelCase\"",
+                                p.display(),
+                                v
+                            ))
+                        }
+                    },
+                }
+            }
+            None => None,
+        };
+        Ok(DeriveConfig { path, rename_all })
+    }
+
+    // Make the crate recompiled when the config file changes. One include in
+    // the crate is enough, so it is only emitted by the first expansion.
+    pub fn track(&self) -> TokenStream {
+        static TRACKED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+        let path = match &self.path {
+            Some(p) => p,
+            None => return quote! {},
+        };
+        let mut tracked = TRACKED.get_or_init(Default::default).lock().unwrap();
+        if !tracked.insert(path.clone()) {
+            return quote! {};
+        }
+        let p = path.to_str().unwrap();
+        quote! { const _: &[u8] = include_bytes!(#p); }
+    }
+}
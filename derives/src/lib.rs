@@ -1,13 +1,13 @@
-use case::{convert_camel_from_pascal, convert_camel_from_snake};
+use gents_derives_internals::case::convert_camel_from_snake;
+use gents_derives_internals::container;
 use proc_macro2::Span;
 use syn::{parse_macro_input, DeriveInput};
-mod case;
-mod container;
+mod config;
 mod serde_json;
-mod symbol;
+mod ts_command;
 mod ts_interface;
 
-use container::{Container, RenameAll};
+use container::{Container, Decl};
 use proc_macro::TokenStream;
 use quote::quote;
 
@@ -34,29 +34,60 @@ pub fn gents_header(attr: TokenStream, item: TokenStream) -> TokenStream {
 #[proc_macro_derive(TS, attributes(ts))]
 pub fn derive_ts(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let container = Container::from_ast(&input);
+    let mut container = Container::from_ast(&input);
+    // Generic types are only generated as the dependencies of other types.
+    if container.export.is_some() && !container.generics.is_empty() {
+        return syn::Error::new_spanned(
+            &input.generics,
+            "export is not supported on generic types",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let config = match config::DeriveConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            return syn::Error::new(Span::call_site(), e)
+                .to_compile_error()
+                .into()
+        }
+    };
+    if container.rename_all.is_none() {
+        container.rename_all = config.rename_all.clone();
+    }
+    let track = config.track();
     let impl_block = get_impl_block(container.clone());
     let serde = serde_json::get_serde_impl_block(container, &input);
     quote! {
         #impl_block
         #serde
+        #track
     }
     .into()
 }
 
 fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
-    let file_name = container.file_name;
+    let ts_name = container.ts_name();
     let is_enum = container.is_enum;
     let rename_all = container.rename_all;
     let ident = container.ident;
     let fields = container.fields;
-    let rename = container.rename;
-    let ts_name = match rename {
-        Some(s) if is_enum => s,
-        _ => ident.to_string(),
+    let (file_name, module_path) = match &container.file_name {
+        Some(f) => (quote! {#f.to_string()}, quote! {None}),
+        None => (
+            quote! {::gents::FileNaming::Module.file_name(module_path!(), #ts_name)},
+            quote! {Some(module_path!().to_string())},
+        ),
     };
     let comments = container.comments;
     let need_builder = container.need_builder;
+    let container_readonly = container.readonly;
+    let internal = container.internal;
+    let decl = match container.decl {
+        Some(Decl::Interface) => quote! {Some(::gents::DeclKind::Interface)},
+        Some(Decl::TypeAlias) => quote! {Some(::gents::DeclKind::TypeAlias)},
+        None => quote! {None},
+    };
     let tag = if let Some(t) = container.tag {
         t
     } else {
@@ -90,27 +121,15 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
             quote! {}
         };
         let field_ds = fields.into_iter().filter(|f| !f.skip).map(|s| {
-            let fi = s.ident;
-            let rename = s.rename;
+            let name = s.ts_name(&rename_all, is_enum);
             let ty = s.ty;
             let field_comments = s.comments;
+            let readonly = container_readonly || s.readonly;
             let tag_value = if let Some(v) = s.tag_value {
                 v
             } else {
                 "".to_string()
             };
-            let name = match (rename, &rename_all) {
-                (None, None) => fi.to_string(),
-                (None, Some(RenameAll::CamelCase)) => {
-                    let s = fi.to_string();
-                    if is_enum {
-                        convert_camel_from_pascal(s)
-                    } else {
-                        convert_camel_from_snake(s)
-                    }
-                }
-                (Some(s), _) => s,
-            };
             if let Some(ty) = ty {
                 quote! {
                     let dep = <#ty as ::gents::TS>::_register(manager, true);
@@ -119,6 +138,8 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
                         ident: #name.to_string(),
                         optional: <#ty as ::gents::TS>::_is_optional(),
                         ts_ty: <#ty as ::gents::TS>::_ts_name(),
+                        ty: Some(<#ty as ::gents::TS>::_type_expr()),
+                        readonly: #readonly,
                         comments: vec![#(#field_comments.to_string()),*],
                         tag_value: #tag_value.to_string(),
                     };
@@ -130,6 +151,8 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
                         ident: #name.to_string(),
                         optional: false,
                         ts_ty: String::from(""),
+                        ty: None,
+                        readonly: #readonly,
                         comments: vec![#(#field_comments.to_string()),*],
                         tag_value: #tag_value.to_string(),
                     };
@@ -142,11 +165,13 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
                 let _enum = ::gents::EnumDescriptor {
                     dependencies: deps,
                     fields,
-                    file_name: #file_name.to_string(),
+                    file_name: #file_name,
                     ts_name: #ts_name.to_string(),
                     comments: vec![#(#comments.to_string()),*],
                     tag: #tag.to_string(),
                     generic,
+                    internal: #internal,
+                    module_path: #module_path,
                 };
                 let descriptor = ::gents::Descriptor::Enum(_enum);
             }
@@ -155,11 +180,14 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
                 let _interface = ::gents::InterfaceDescriptor {
                     dependencies: deps,
                     fields,
-                    file_name: #file_name.to_string(),
+                    file_name: #file_name,
                     ts_name: #ts_name.to_string(),
                     comments: vec![#(#comments.to_string()),*],
                     need_builder: #need_builder,
                     generic,
+                    decl: #decl,
+                    internal: #internal,
+                    module_path: #module_path,
                 };
                 let descriptor = ::gents::Descriptor::Interface(_interface);
             }
@@ -184,6 +212,19 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
         .generics
         .iter()
         .map(|g| quote! {generics_names.push(<#g as ::gents::TS>::_ts_name())});
+    let type_expr_args = container
+        .generics
+        .iter()
+        .map(|g| quote! {<#g as ::gents::TS>::_type_expr()});
+    let type_expr_func = quote! {
+        fn _type_expr() -> ::gents::TypeExpr {
+            ::gents::TypeExpr::Named {
+                name: #ts_name.to_string(),
+                file_name: #file_name,
+                args: vec![#(#type_expr_args),*],
+            }
+        }
+    };
     let ts_name_func = if container.generics.is_empty() {
         quote! {
             fn _ts_name() -> String {
@@ -202,11 +243,27 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
         }
     };
     if container.generics.is_empty() {
+        let export = container.export.map(|tags| {
+            quote! {
+                ::gents::inventory::submit! {
+                    ::gents::_Exported {
+                        name: #ts_name,
+                        module_path: module_path!(),
+                        tags: &[#(#tags),*],
+                        register: |manager| {
+                            <#ident as ::gents::TS>::_register(manager, true);
+                        },
+                    }
+                }
+            }
+        });
         quote! {
             impl ::gents::TS for #ident {
                 #register_func
                 #ts_name_func
+                #type_expr_func
             }
+            #export
         }
     } else {
         let generics_ts = container.generics.iter().map(|g| {
@@ -224,6 +281,7 @@ fn get_impl_block(container: Container) -> proc_macro2::TokenStream {
             ::gents::TS for #ident<#(#generics_idents),*>{
                 #register_func
                 #ts_name_func
+                #type_expr_func
             }
         }
     }
@@ -252,6 +310,9 @@ fn get_generic_placeholder(
             fn _ts_name() -> String {
                 #ts_name.to_string()
             }
+            fn _type_expr() -> ::gents::TypeExpr {
+                ::gents::TypeExpr::Param(#ts_name.to_string())
+            }
         }
     }
 }
@@ -260,3 +321,8 @@ fn get_generic_placeholder(
 pub fn ts_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
     ts_interface::ts_interface(attr, item)
 }
+
+#[proc_macro_attribute]
+pub fn ts_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    ts_command::ts_command(attr, item)
+}
@@ -0,0 +1,222 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Error, Ident, ItemFn, LitStr, Result, Token, Type,
+};
+
+use crate::convert_camel_from_snake;
+use crate::ts_interface::{extract_doc, strip_reference};
+
+// The args injected by Tauri, which are not passed to `invoke`.
+const INJECTED_TYPES: &[&str] = &[
+    "State",
+    "AppHandle",
+    "Window",
+    "WebviewWindow",
+    "Webview",
+    "Request",
+];
+
+/// #[ts_command(file_name = "commands.ts")]
+/// #[ts_command(file_name = "commands.ts", export = "desktop")]
+pub fn ts_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as TsCommandArgs);
+    let func = parse_macro_input!(item as ItemFn);
+
+    match expand_ts_command(args, func) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+struct TsCommandArgs {
+    file_name: String,
+    // Tags of `export = "..."`.
+    tags: Vec<String>,
+}
+
+impl Parse for TsCommandArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut file_name: Option<String> = None;
+        let mut tags = Vec::new();
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let lit: LitStr = input.parse()?;
+
+            if ident == "file_name" {
+                file_name = Some(lit.value());
+            } else if ident == "export" {
+                tags.push(lit.value());
+            } else {
+                return Err(Error::new_spanned(
+                    ident,
+                    "expected `file_name = \"...\"` or `export = \"...\"`",
+                ));
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        let file_name =
+            file_name.ok_or_else(|| input.error("`file_name = \"...\"` is required"))?;
+
+        Ok(Self { file_name, tags })
+    }
+}
+
+// The commands are always exported, since they are functions which can not be
+// added by `FileGroup::add`.
+fn expand_ts_command(args: TsCommandArgs, func: ItemFn) -> Result<proc_macro2::TokenStream> {
+    let name = func.sig.ident.to_string();
+    let file_name = args.file_name;
+    let tags = args.tags;
+    let snake_case = tauri_snake_case(&func.attrs)?;
+
+    let comments = func
+        .attrs
+        .iter()
+        .filter_map(extract_doc)
+        .map(|d| d.trim_start().to_string())
+        .collect::<Vec<_>>();
+
+    let mut params = Vec::new();
+    for arg in &func.sig.inputs {
+        let pat = match arg {
+            syn::FnArg::Receiver(r) => {
+                return Err(Error::new_spanned(r, "a command can not take `self`"));
+            }
+            syn::FnArg::Typed(pat) => pat,
+        };
+        let ty = strip_reference(&pat.ty);
+        if is_injected(ty) {
+            continue;
+        }
+        let ident = match &*pat.pat {
+            syn::Pat::Ident(i) => i.ident.to_string(),
+            _ => {
+                return Err(Error::new_spanned(
+                    &pat.pat,
+                    "unsupported parameter pattern",
+                ))
+            }
+        };
+        // Tauri takes the args in camel case unless `rename_all` is set.
+        let ident = if snake_case {
+            ident
+        } else {
+            convert_camel_from_snake(ident)
+        };
+        params.push(quote! {
+            (#ident.to_string(), {
+                <#ty as ::gents::TS>::_register(manager, true);
+                <#ty as ::gents::TS>::_type_expr()
+            })
+        });
+    }
+
+    let return_type = match &func.sig.output {
+        // The errors are rejected by `invoke` rather than returned.
+        syn::ReturnType::Type(_, ty) => match result_ok(strip_reference(ty)) {
+            Some(ok) => quote! {
+                {
+                    <#ok as ::gents::TS>::_register(manager, true);
+                    Some(<#ok as ::gents::TS>::_type_expr())
+                }
+            },
+            None => quote! { None },
+        },
+        syn::ReturnType::Default => quote! { None },
+    };
+
+    Ok(quote! {
+        #func
+
+        ::gents::inventory::submit! {
+            ::gents::_Exported {
+                name: #name,
+                module_path: module_path!(),
+                tags: &[#(#tags),*],
+                register: |manager| {
+                    let descriptor = ::gents::CommandDescriptor {
+                        name: #name.to_string(),
+                        file_name: #file_name.to_string(),
+                        params: vec![ #(#params),* ],
+                        return_type: #return_type,
+                        comment: vec![ #( #comments.to_string() ),* ],
+                    };
+                    manager.add_command(descriptor);
+                },
+            }
+        }
+    })
+}
+
+// `#[tauri::command(rename_all = "snake_case")]` keeps the names of the args.
+// It is only seen if `#[ts_command]` is above it.
+fn tauri_snake_case(attrs: &[syn::Attribute]) -> Result<bool> {
+    let mut snake_case = false;
+    for attr in attrs {
+        let is_command = attr
+            .path()
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "command");
+        if !is_command || !matches!(attr.meta, syn::Meta::List(_)) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let lit: LitStr = meta.value()?.parse()?;
+                snake_case = lit.value() == "snake_case";
+            } else if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                // Skip the values of the other options like `async`.
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(snake_case)
+}
+
+fn is_injected(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| INJECTED_TYPES.contains(&s.ident.to_string().as_str())),
+        _ => false,
+    }
+}
+
+// `Result<T, E>` -> `T`. The unit type is `None`, which is `void` like the
+// commands returning nothing.
+fn result_ok(ty: &Type) -> Option<&Type> {
+    let ok = result_arg(ty).unwrap_or(ty);
+    match ok {
+        Type::Tuple(t) if t.elems.is_empty() => None,
+        _ => Some(ok),
+    }
+}
+
+// `T` of `Result<T, E>`, or of `Result<T>` like `tauri::Result`.
+fn result_arg(ty: &Type) -> Option<&Type> {
+    let Type::Path(p) = ty else { return None };
+    let seg = p.path.segments.last()?;
+    if seg.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &seg.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|a| match a {
+        syn::GenericArgument::Type(t) => Some(t),
+        _ => None,
+    })
+}
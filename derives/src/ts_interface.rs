@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     parse_macro_input, Error, Ident, ImplItem, ImplItemFn, ItemImpl, LitStr, Result, Token, Type,
 };
@@ -8,6 +9,9 @@ use syn::{
 use crate::convert_camel_from_snake;
 
 /// #[ts_interface(file_name = "a.ts")]
+/// #[ts_interface(file_name = "a.ts", export = "frontend")]
+/// #[ts_interface(file_name = "a.ts", dispatch)]
+/// #[ts_interface(file_name = "a.ts", wasm)]
 pub fn ts_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as TsInterfaceArgs);
     let impl_block = parse_macro_input!(item as ItemImpl);
@@ -22,6 +26,12 @@ struct TsInterfaceArgs {
     file_name: String,
     ident: Option<String>,
     async_func: bool,
+    // Tags of `export = "..."`. None if the API is not exported.
+    export: Option<Vec<String>>,
+    // Generate `gents_dispatch` to call the methods by their names.
+    dispatch: bool,
+    // Export the methods by wasm-bindgen.
+    wasm: bool,
 }
 
 impl Parse for TsInterfaceArgs {
@@ -29,9 +39,28 @@ impl Parse for TsInterfaceArgs {
         let mut file_name: Option<String> = None;
         let mut ident_val: Option<String> = None;
         let mut async_func: bool = false;
+        let mut export: Option<Vec<String>> = None;
+        let mut dispatch = false;
+        let mut wasm = false;
 
         while !input.is_empty() {
-            let ident: Ident = input.parse()?;
+            // `async` is a keyword, which is not parsed as an `Ident`.
+            let ident = Ident::parse_any(input)?;
+            let flag = !input.peek(Token![=]);
+            if flag && (ident == "export" || ident == "dispatch" || ident == "wasm") {
+                if ident == "export" {
+                    export.get_or_insert_with(Vec::new);
+                } else if ident == "dispatch" {
+                    dispatch = true;
+                } else {
+                    wasm = true;
+                }
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+                continue;
+            }
             input.parse::<Token![=]>()?;
             let lit: LitStr = input.parse()?;
 
@@ -41,10 +70,12 @@ impl Parse for TsInterfaceArgs {
                 ident_val = Some(lit.value());
             } else if ident == "async" {
                 async_func = lit.value() == "true";
+            } else if ident == "export" {
+                export.get_or_insert_with(Vec::new).push(lit.value());
             } else {
                 return Err(Error::new_spanned(
                     ident,
-                    "expected `file_name = \"...\"` or `ident = \"...\"` or `async = \"true|false\"`",
+                    "expected `file_name = \"...\"` or `ident = \"...\"` or `async = \"true|false\"` or `export` or `dispatch` or `wasm`",
                 ));
             }
 
@@ -61,13 +92,16 @@ impl Parse for TsInterfaceArgs {
             file_name,
             ident: ident_val,
             async_func,
+            export,
+            dispatch,
+            wasm,
         })
     }
 }
 
 fn expand_ts_interface(
     args: TsInterfaceArgs,
-    impl_block: ItemImpl,
+    mut impl_block: ItemImpl,
 ) -> Result<proc_macro2::TokenStream> {
     // Reject trait impl
     if impl_block.trait_.is_some() {
@@ -84,30 +118,73 @@ fn expand_ts_interface(
     let type_name = args.ident.unwrap_or_else(|| self_ty_ident.to_string());
     let file_name = args.file_name;
     let async_func = args.async_func;
+    let export = args.export.map(|tags| {
+        quote! {
+            ::gents::inventory::submit! {
+                ::gents::_Exported {
+                    name: #type_name,
+                    module_path: module_path!(),
+                    tags: &[#(#tags),*],
+                    register: |manager| {
+                        manager.add_api_descriptor(
+                            <#self_ty_ident as ::gents::_TsAPI>::__get_api_descriptor(),
+                        );
+                    },
+                }
+            }
+        }
+    });
 
     let impl_comments = Vec::<String>::new();
     // TODO: collect impl-level doc comments
 
     // Collect public methods
     let mut method_tokens = Vec::new();
+    let mut dispatch_arms = Vec::new();
 
-    for item in &impl_block.items {
+    for item in &mut impl_block.items {
         let ImplItem::Fn(func) = item else { continue };
 
+        // `#[ts(...)]` is not a real attribute, remove it from the output.
+        let http = parse_method_attrs(&func.attrs)?;
+        func.attrs.retain(|a| !a.path().is_ident("ts"));
+
         // Only `pub fn`
         if !matches!(func.vis, syn::Visibility::Public(_)) {
             continue;
         }
 
-        method_tokens.push(expand_method(func)?);
+        method_tokens.push(expand_method(func, http)?);
+        if args.dispatch {
+            dispatch_arms.push(expand_dispatch_arm(func)?);
+        }
     }
 
+    let dispatch = if args.dispatch {
+        Some(expand_dispatch(&impl_block, &type_name, &dispatch_arms))
+    } else {
+        None
+    };
+    let wasm = if args.wasm {
+        let funcs = impl_block
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(func) if matches!(func.vis, syn::Visibility::Public(_)) => Some(func),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Some(expand_wasm(&impl_block, self_ty_ident, &type_name, &funcs)?)
+    } else {
+        None
+    };
+
     let expanded = quote! {
         #impl_block
 
-        impl gents::_TsAPI for #self_ty_ident {
-            fn __get_api_descriptor() -> gents::ApiDescriptor {
-                gents::ApiDescriptor {
+        impl ::gents::_TsAPI for #self_ty_ident {
+            fn __get_api_descriptor() -> ::gents::ApiDescriptor {
+                ::gents::ApiDescriptor {
                     name: #type_name.to_string(),
                     file_name: #file_name.to_string(),
                     methods: vec![ #(#method_tokens),* ],
@@ -116,6 +193,12 @@ fn expand_ts_interface(
                 }
             }
         }
+
+        #export
+
+        #dispatch
+
+        #wasm
     };
 
     Ok(expanded)
@@ -136,7 +219,7 @@ fn extract_self_type_ident(ty: &Type) -> Result<&Ident> {
     }
 }
 
-fn extract_doc(attr: &syn::Attribute) -> Option<String> {
+pub(crate) fn extract_doc(attr: &syn::Attribute) -> Option<String> {
     if !attr.path().is_ident("doc") {
         return None;
     }
@@ -154,7 +237,36 @@ fn extract_doc(attr: &syn::Attribute) -> Option<String> {
     }
 }
 
-fn expand_method(func: &ImplItemFn) -> Result<proc_macro2::TokenStream> {
+const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// #[ts(http = "POST /users")]
+fn parse_method_attrs(attrs: &[syn::Attribute]) -> Result<Option<String>> {
+    let mut http = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("ts")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("http") {
+                return Err(meta.error("expected `http = \"METHOD /path\"`"));
+            }
+            let lit: LitStr = meta.value()?.parse()?;
+            let value = lit.value();
+            let valid = match value.split_once(' ') {
+                Some((method, path)) => HTTP_METHODS.contains(&method) && path.starts_with('/'),
+                None => false,
+            };
+            if !valid {
+                return Err(Error::new_spanned(
+                    &lit,
+                    "expected `http = \"METHOD /path\"`, like `http = \"POST /users\"`",
+                ));
+            }
+            http = Some(value);
+            Ok(())
+        })?;
+    }
+    Ok(http)
+}
+
+fn expand_method(func: &ImplItemFn, http: Option<String>) -> Result<proc_macro2::TokenStream> {
     let name = convert_camel_from_snake(func.sig.ident.to_string());
 
     // Collect method-level doc comments
@@ -168,6 +280,8 @@ fn expand_method(func: &ImplItemFn) -> Result<proc_macro2::TokenStream> {
 
     // Parameters
     let mut params = Vec::new();
+    let mut param_types = Vec::new();
+    let mut param_registers = Vec::new();
 
     for arg in &func.sig.inputs {
         match arg {
@@ -190,6 +304,12 @@ fn expand_method(func: &ImplItemFn) -> Result<proc_macro2::TokenStream> {
                 params.push(quote! {
                     (#ident.to_string(), std::any::TypeId::of::<#ty>())
                 });
+                param_types.push(quote! {
+                    <#ty as ::gents::TS>::_type_expr()
+                });
+                param_registers.push(quote! {
+                    <#ty as ::gents::TS>::_register
+                });
             }
         }
     }
@@ -203,30 +323,37 @@ fn expand_method(func: &ImplItemFn) -> Result<proc_macro2::TokenStream> {
         syn::ReturnType::Default => None,
     };
 
-    let res = if let Some(ty) = ret_ty {
-        quote! {
-            gents::MethodDescriptor {
-                name: #name.to_string(),
-                params: vec![ #(#params),* ],
-                comment: vec![ #( #comments.to_string() ),* ],
-                return_type: Some(std::any::TypeId::of::<#ty>()),
-            }
-        }
-    } else {
-        quote! {
-            gents::MethodDescriptor {
-                name: #name.to_string(),
-                params: vec![ #(#params),* ],
-                comment: vec![ #( #comments.to_string() ),* ],
-                return_type: None,
-            }
+    let http = match http {
+        Some(h) => quote! { Some(#h.to_string()) },
+        None => quote! { None },
+    };
+    let (return_type, return_type_expr, return_register) = match ret_ty {
+        Some(ty) => (
+            quote! { Some(std::any::TypeId::of::<#ty>()) },
+            quote! { Some(<#ty as ::gents::TS>::_type_expr()) },
+            quote! { Some(<#ty as ::gents::TS>::_register) },
+        ),
+        None => (quote! { None }, quote! { None }, quote! { None }),
+    };
+
+    let res = quote! {
+        ::gents::MethodDescriptor {
+            name: #name.to_string(),
+            params: vec![ #(#params),* ],
+            comment: vec![ #( #comments.to_string() ),* ],
+            return_type: #return_type,
+            param_types: vec![ #(#param_types),* ],
+            return_type_expr: #return_type_expr,
+            param_registers: vec![ #(#param_registers),* ],
+            return_register: #return_register,
+            http: #http,
         }
     };
 
     Ok(res)
 }
 
-fn strip_reference(ty: &syn::Type) -> &syn::Type {
+pub(crate) fn strip_reference(ty: &syn::Type) -> &syn::Type {
     match ty {
         syn::Type::Reference(r) => {
             if let syn::Type::Path(p) = &*r.elem {
@@ -241,3 +368,334 @@ fn strip_reference(ty: &syn::Type) -> &syn::Type {
         _ => ty,
     }
 }
+
+// The arm of the method in `gents_dispatch`, and whether it is async and takes
+// `&mut self`.
+struct DispatchArm {
+    tokens: proc_macro2::TokenStream,
+    is_async: bool,
+    mut_self: bool,
+}
+
+fn expand_dispatch(
+    impl_block: &ItemImpl,
+    type_name: &str,
+    arms: &[DispatchArm],
+) -> proc_macro2::TokenStream {
+    let self_ty = &impl_block.self_ty;
+    let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+    let asyncness = if arms.iter().any(|a| a.is_async) {
+        quote! { async }
+    } else {
+        quote! {}
+    };
+    let receiver = if arms.iter().any(|a| a.mut_self) {
+        quote! { &mut self }
+    } else {
+        quote! { &self }
+    };
+    let prefix = format!("{}.", type_name);
+    let arms = arms.iter().map(|a| &a.tokens);
+    quote! {
+        impl #impl_generics #self_ty #where_clause {
+            /// Call the method by its Typescript name, like `getUser` or
+            /// `UserApi.getUser`, with the JSON array of its params, and return
+            /// the JSON of its return value.
+            pub #asyncness fn gents_dispatch(
+                #receiver,
+                method: &str,
+                params: ::gents::serde_json::Value,
+            ) -> ::std::result::Result<::gents::serde_json::Value, ::gents::DispatchError> {
+                let method = method.strip_prefix(#prefix).unwrap_or(method);
+                match method {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(
+                        ::gents::DispatchError::UnknownMethod(method.to_string()),
+                    ),
+                }
+            }
+        }
+    }
+}
+
+// A param bound to `__p{idx}` before the method is called by `gents_dispatch` or
+// the wasm wrappers. The references are deserialized into the owned values.
+struct CallParam {
+    ident: Ident,
+    // The name in Typescript.
+    name: String,
+    var: Ident,
+    ty: proc_macro2::TokenStream,
+    mutable: bool,
+}
+
+// The call of a method from the deserialized params.
+struct MethodCall {
+    params: Vec<CallParam>,
+    // `self.f(..)`, or `Self::f(..)` if the method has no receiver.
+    call: proc_macro2::TokenStream,
+    is_async: bool,
+    mut_self: bool,
+    returns_result: bool,
+}
+
+fn parse_call(func: &ImplItemFn) -> Result<MethodCall> {
+    let fn_ident = &func.sig.ident;
+    let mut receiver = None;
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for arg in &func.sig.inputs {
+        let pat = match arg {
+            syn::FnArg::Receiver(r) => {
+                receiver = Some(r.mutability.is_some());
+                continue;
+            }
+            syn::FnArg::Typed(pat) => pat,
+        };
+        let syn::Pat::Ident(i) = &*pat.pat else {
+            return Err(Error::new_spanned(
+                &pat.pat,
+                "unsupported parameter pattern",
+            ));
+        };
+        let var = quote::format_ident!("__p{}", params.len());
+        let (ty, mutable) = match &*pat.ty {
+            syn::Type::Reference(r) => {
+                let mutable = r.mutability.is_some();
+                if mutable {
+                    args.push(quote! { &mut #var });
+                } else {
+                    args.push(quote! { &#var });
+                }
+                (owned_type(&r.elem), mutable)
+            }
+            ty => {
+                args.push(quote! { #var });
+                (quote! { #ty }, false)
+            }
+        };
+        params.push(CallParam {
+            ident: i.ident.clone(),
+            name: convert_camel_from_snake(i.ident.to_string()),
+            var,
+            ty,
+            mutable,
+        });
+    }
+    let call = match receiver {
+        Some(_) => quote! { self.#fn_ident(#(#args),*) },
+        None => quote! { Self::#fn_ident(#(#args),*) },
+    };
+    let is_async = func.sig.asyncness.is_some();
+    let call = if is_async {
+        quote! { #call.await }
+    } else {
+        call
+    };
+    Ok(MethodCall {
+        params,
+        call,
+        is_async,
+        mut_self: receiver == Some(true),
+        returns_result: returns_result(&func.sig.output),
+    })
+}
+
+fn expand_dispatch_arm(func: &ImplItemFn) -> Result<DispatchArm> {
+    let name = convert_camel_from_snake(func.sig.ident.to_string());
+    let MethodCall {
+        params,
+        call,
+        is_async,
+        mut_self,
+        returns_result,
+    } = parse_call(func)?;
+    let lets = params.iter().enumerate().map(|(idx, p)| {
+        let CallParam {
+            name,
+            var,
+            ty,
+            mutable,
+            ..
+        } = p;
+        let mutability = if *mutable {
+            quote! { mut }
+        } else {
+            quote! {}
+        };
+        quote! {
+            let #mutability #var: #ty =
+                ::gents::_dispatch_param(method, &mut __params, #idx, #name)?;
+        }
+    });
+    let len = params.len();
+    // The `Err` of the methods returning `Result` is the error of the call.
+    let ret = if returns_result {
+        quote! {
+            match #call {
+                ::std::result::Result::Ok(v) => ::gents::_dispatch_result(v),
+                ::std::result::Result::Err(e) => {
+                    ::std::result::Result::Err(::gents::_dispatch_err(e))
+                }
+            }
+        }
+    } else {
+        quote! { ::gents::_dispatch_result(#call) }
+    };
+    let tokens = quote! {
+        #name => {
+            #[allow(unused_mut)]
+            let mut __params = ::gents::_dispatch_params(method, params, #len)?;
+            #(#lets)*
+            #ret
+        }
+    };
+    Ok(DispatchArm {
+        tokens,
+        is_async,
+        mut_self,
+    })
+}
+
+// The methods exported to JS by wasm-bindgen, which take and return `JsValue`s
+// converted by serde-wasm-bindgen. Their Typescript signatures are skipped and
+// come from the API interface, which is merged into the class.
+fn expand_wasm(
+    impl_block: &ItemImpl,
+    self_ident: &Ident,
+    type_name: &str,
+    funcs: &[&ImplItemFn],
+) -> Result<proc_macro2::TokenStream> {
+    let self_ty = &impl_block.self_ty;
+    let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+    let mut wrappers = Vec::new();
+    for func in funcs {
+        let js_name = convert_camel_from_snake(func.sig.ident.to_string());
+        let wrapper = quote::format_ident!("__gents_wasm_{}", func.sig.ident);
+        let MethodCall {
+            params,
+            call,
+            is_async,
+            mut_self,
+            returns_result,
+        } = parse_call(func)?;
+        // The futures of wasm-bindgen must be `'static`, so they can not
+        // borrow `self`.
+        if let (true, Some(r)) = (is_async, func.sig.receiver()) {
+            return Err(Error::new_spanned(
+                r,
+                "async methods taking `self` are not supported by `wasm`, since wasm-bindgen requires `'static` futures",
+            ));
+        }
+        let receiver = match func.sig.receiver() {
+            Some(_) if mut_self => quote! { &mut self, },
+            Some(_) => quote! { &self, },
+            None => quote! {},
+        };
+        let inputs = params.iter().map(|p| {
+            let ident = &p.ident;
+            quote! { #ident: ::gents::wasm_bindgen::JsValue }
+        });
+        let lets = params.iter().map(|p| {
+            let CallParam {
+                ident,
+                var,
+                ty,
+                mutable,
+                ..
+            } = p;
+            let mutability = if *mutable {
+                quote! { mut }
+            } else {
+                quote! {}
+            };
+            quote! {
+                let #mutability #var: #ty = ::gents::serde_wasm_bindgen::from_value(#ident)?;
+            }
+        });
+        let asyncness = if is_async {
+            quote! { async }
+        } else {
+            quote! {}
+        };
+        // The maps are converted into `Map`s, as declared by the interface.
+        let to_js = |v: proc_macro2::TokenStream| {
+            quote! {
+                ::gents::serde::Serialize::serialize(
+                    &#v,
+                    &::gents::serde_wasm_bindgen::Serializer::new(),
+                )?
+            }
+        };
+        let ret = if returns_result {
+            let ok = to_js(quote! { v });
+            let err = to_js(quote! { e });
+            quote! {
+                match #call {
+                    ::std::result::Result::Ok(v) => ::std::result::Result::Ok(#ok),
+                    ::std::result::Result::Err(e) => ::std::result::Result::Err(#err),
+                }
+            }
+        } else {
+            let ok = to_js(quote! { __ret });
+            quote! {
+                let __ret = #call;
+                ::std::result::Result::Ok(#ok)
+            }
+        };
+        wrappers.push(quote! {
+            #[wasm_bindgen(js_name = #js_name, skip_typescript)]
+            pub #asyncness fn #wrapper(
+                #receiver
+                #(#inputs),*
+            ) -> ::std::result::Result<::gents::wasm_bindgen::JsValue, ::gents::wasm_bindgen::JsValue> {
+                #(#lets)*
+                #ret
+            }
+        });
+    }
+    // The API interface has the same name as the class if `ident` is not set.
+    let merge = if self_ident == type_name {
+        None
+    } else {
+        let section =
+            quote::format_ident!("__GENTS_WASM_{}", self_ident.to_string().to_uppercase());
+        let ts = format!("export interface {} extends {} {{}}", self_ident, type_name);
+        Some(quote! {
+            #[::gents::wasm_bindgen::prelude::wasm_bindgen(
+                wasm_bindgen = ::gents::wasm_bindgen,
+                typescript_custom_section,
+            )]
+            const #section: &'static str = #ts;
+        })
+    };
+    Ok(quote! {
+        #[::gents::wasm_bindgen::prelude::wasm_bindgen(
+            wasm_bindgen = ::gents::wasm_bindgen,
+            wasm_bindgen_futures = ::gents::wasm_bindgen_futures,
+        )]
+        impl #impl_generics #self_ty #where_clause {
+            #(#wrappers)*
+        }
+
+        #merge
+    })
+}
+
+// `str` -> `String`
+fn owned_type(ty: &syn::Type) -> proc_macro2::TokenStream {
+    match ty {
+        syn::Type::Path(p) if p.path.is_ident("str") => quote! { ::std::string::String },
+        ty => quote! { #ty },
+    }
+}
+
+fn returns_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(p) => p.path.segments.last().is_some_and(|s| s.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
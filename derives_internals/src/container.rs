@@ -7,16 +7,21 @@ use syn::Meta;
 use syn::MetaNameValue;
 use syn::Type;
 
-use crate::case::convert_camel_from_pascal;
-use crate::symbol::BUILDER;
-use crate::symbol::DEFAULT;
-use crate::symbol::FILE_NAME;
-use crate::symbol::TAG;
-use crate::symbol::{RENAME, RENAME_ALL, SKIP, TS};
+use super::case::{convert_camel_from_pascal, convert_camel_from_snake};
+use super::symbol::BUILDER;
+use super::symbol::DECL;
+use super::symbol::DEFAULT;
+use super::symbol::EXPORT;
+use super::symbol::FILE_NAME;
+use super::symbol::INTERNAL;
+use super::symbol::READONLY;
+use super::symbol::TAG;
+use super::symbol::{RENAME, RENAME_ALL, SKIP, TS};
 
 #[derive(Clone)]
 pub struct Container<'a> {
-    pub file_name: String,
+    // Derived from the module path if not specified.
+    pub file_name: Option<String>,
     pub is_enum: bool,
     pub fields: Vec<Field<'a>>,
     pub rename_all: Option<RenameAll>,
@@ -26,15 +31,32 @@ pub struct Container<'a> {
     pub need_builder: bool,
     pub generics: Vec<&'a Ident>,
     pub tag: Option<String>,
+    pub readonly: bool,
+    pub decl: Option<Decl>,
+    pub internal: bool,
+    // Tags of `#[ts(export = "...")]`. None if the type is not exported.
+    pub export: Option<Vec<String>>,
 }
 
 impl<'a> Container<'a> {
+    // The name in Typescript. Only enums can be renamed.
+    pub fn ts_name(&self) -> String {
+        match &self.rename {
+            Some(s) if self.is_enum => s.to_string(),
+            _ => self.ident.to_string(),
+        }
+    }
+
     pub fn from_ast(item: &'a syn::DeriveInput) -> Self {
         let mut rename_all: Option<RenameAll> = None;
         let mut file_name: Option<String> = None;
         let mut rename: Option<String> = None;
         let mut need_builder = false;
         let mut tag: Option<String> = None;
+        let mut readonly = false;
+        let mut decl: Option<Decl> = None;
+        let mut internal = false;
+        let mut export: Option<Vec<String>> = None;
         let generics = item
             .generics
             .params
@@ -56,17 +78,19 @@ impl<'a> Container<'a> {
                 Meta::Path(path) => {
                     if path == BUILDER {
                         need_builder = true;
+                    } else if path == READONLY {
+                        readonly = true;
+                    } else if path == INTERNAL {
+                        internal = true;
+                    } else if path == EXPORT {
+                        export.get_or_insert_with(Vec::new);
                     }
                 }
                 Meta::List(_) => panic!("unexpected meta list"),
                 Meta::NameValue(m) => {
                     if m.path == RENAME_ALL {
                         let s = get_lit_str(&m.value).expect("rename_all requires lit str");
-                        let t = match s.value().as_str() {
-                            "camelCase" => RenameAll::CamelCase,
-                            _ => panic!("unexpected literal for case converting"),
-                        };
-                        rename_all = Some(t);
+                        rename_all = Some(RenameAll::parse(&s.value()));
                     } else if m.path == FILE_NAME {
                         let s = get_lit_str(&m.value).expect("file_name requires lit str");
                         file_name = Some(s.value());
@@ -76,6 +100,17 @@ impl<'a> Container<'a> {
                     } else if m.path == TAG {
                         let s = get_lit_str(&m.value).expect("tag requires lit str");
                         tag = Some(s.value());
+                    } else if m.path == DECL {
+                        let s = get_lit_str(&m.value).expect("decl requires lit str");
+                        let d = match s.value().as_str() {
+                            "interface" => Decl::Interface,
+                            "type" => Decl::TypeAlias,
+                            _ => panic!("unexpected literal for decl, use `interface` or `type`"),
+                        };
+                        decl = Some(d);
+                    } else if m.path == EXPORT {
+                        let s = get_lit_str(&m.value).expect("export requires lit str");
+                        export.get_or_insert_with(Vec::new).push(s.value());
                     } else {
                         panic!("unexpected attr")
                     }
@@ -93,7 +128,7 @@ impl<'a> Container<'a> {
                     .map(|f| Field::from_field(f))
                     .collect::<Vec<_>>();
                 Container {
-                    file_name: file_name.expect("file name is required"),
+                    file_name,
                     is_enum: false,
                     fields,
                     rename_all,
@@ -103,19 +138,29 @@ impl<'a> Container<'a> {
                     need_builder,
                     generics,
                     tag,
+                    readonly,
+                    decl,
+                    internal,
+                    export,
                 }
             }
             syn::Data::Enum(e) => {
                 if need_builder {
                     panic!("enum does not support builder");
                 }
+                if readonly {
+                    panic!("enum does not support readonly");
+                }
+                if decl.is_some() {
+                    panic!("enum does not support decl");
+                }
                 let fields = e
                     .variants
                     .iter()
                     .map(|v| Field::from_variant(v))
                     .collect::<Vec<_>>();
                 Container {
-                    file_name: file_name.unwrap(),
+                    file_name,
                     is_enum: true,
                     fields,
                     rename_all,
@@ -125,6 +170,10 @@ impl<'a> Container<'a> {
                     need_builder,
                     tag,
                     generics,
+                    readonly,
+                    decl,
+                    internal,
+                    export,
                 }
             }
             _ => panic!("gents does not support the union type currently, use struct instead"),
@@ -141,9 +190,26 @@ pub struct Field<'a> {
     pub comments: Vec<String>,
     pub tag_value: Option<String>,
     pub default: Option<String>,
+    pub readonly: bool,
 }
 
 impl<'a> Field<'a> {
+    // The name of the field, or the variant, in Typescript.
+    pub fn ts_name(&self, rename_all: &Option<RenameAll>, is_enum: bool) -> String {
+        match (&self.rename, rename_all) {
+            (None, None) => self.ident.to_string(),
+            (None, Some(RenameAll::CamelCase)) => {
+                let s = self.ident.to_string();
+                if is_enum {
+                    convert_camel_from_pascal(s)
+                } else {
+                    convert_camel_from_snake(s)
+                }
+            }
+            (Some(s), _) => s.to_string(),
+        }
+    }
+
     pub fn from_field(f: &'a syn::Field) -> Self {
         let comments = parse_comments(&f.attrs);
         let attrs = parse_attrs(&f.attrs);
@@ -155,6 +221,7 @@ impl<'a> Field<'a> {
             comments,
             tag_value: attrs.tag_value,
             default: attrs.default,
+            readonly: attrs.readonly,
         }
     }
 
@@ -183,6 +250,7 @@ impl<'a> Field<'a> {
             comments,
             tag_value,
             default: attrs.default,
+            readonly: attrs.readonly,
         }
     }
 }
@@ -192,12 +260,23 @@ fn parse_attrs<'a>(attrs: &'a Vec<Attribute>) -> FieldAttrs {
     let mut rename: Option<String> = None;
     let mut tag_value: Option<String> = None;
     let mut default: Option<String> = None;
+    let mut readonly = false;
     for meta_item in attrs
         .iter()
-        .flat_map(|attr| get_ts_meta_name_value_items(attr))
+        .flat_map(|attr| get_ts_meta_items(attr))
         .flatten()
     {
-        let m = meta_item;
+        let m = match meta_item {
+            Meta::Path(path) => {
+                if path == READONLY {
+                    readonly = true;
+                    continue;
+                }
+                panic!("unexpected attr")
+            }
+            Meta::List(_) => panic!("unexpected meta list"),
+            Meta::NameValue(m) => m,
+        };
         if m.path == RENAME {
             if let Ok(s) = get_lit_str(&m.value) {
                 rename = Some(s.value());
@@ -222,6 +301,7 @@ fn parse_attrs<'a>(attrs: &'a Vec<Attribute>) -> FieldAttrs {
         rename,
         tag_value,
         default,
+        readonly,
     }
 }
 
@@ -230,6 +310,7 @@ struct FieldAttrs {
     rename: Option<String>,
     tag_value: Option<String>,
     default: Option<String>,
+    readonly: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -237,23 +318,27 @@ pub enum RenameAll {
     CamelCase,
 }
 
-fn get_ts_meta_items(attr: &syn::Attribute) -> Result<Vec<syn::Meta>, ()> {
-    if attr.path() != TS {
-        return Ok(Vec::new());
+impl RenameAll {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "camelCase" => RenameAll::CamelCase,
+            _ => panic!("unexpected literal for case converting"),
+        }
     }
+}
 
-    match attr.parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated) {
-        Ok(name_values) => Ok(name_values.into_iter().collect()),
-        Err(_) => Err(()),
-    }
+#[derive(Debug, Clone)]
+pub enum Decl {
+    Interface,
+    TypeAlias,
 }
 
-fn get_ts_meta_name_value_items(attr: &syn::Attribute) -> Result<Vec<syn::MetaNameValue>, ()> {
+fn get_ts_meta_items(attr: &syn::Attribute) -> Result<Vec<syn::Meta>, ()> {
     if attr.path() != TS {
         return Ok(Vec::new());
     }
 
-    match attr.parse_args_with(Punctuated::<MetaNameValue, Comma>::parse_terminated) {
+    match attr.parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated) {
         Ok(name_values) => Ok(name_values.into_iter().collect()),
         Err(_) => Err(()),
     }
@@ -293,7 +378,7 @@ fn parse_comments(attrs: &[Attribute]) -> Vec<String> {
     result
 }
 
-pub(crate) struct GentsWasmAttrs {
+pub struct GentsWasmAttrs {
     file_name: String,
 }
 
@@ -0,0 +1,8 @@
+//! # gents_derives_internals
+//! The parsing of the `#[ts(...)]` attributes, shared by the `TS` derive of
+//! `gents_derives` and the source converter of `gents::build`, so that both
+//! handle the types in the same way.
+
+pub mod case;
+pub mod container;
+pub mod symbol;
@@ -12,6 +12,10 @@ pub const SKIP: Symbol = Symbol("skip");
 pub const BUILDER: Symbol = Symbol("builder");
 pub const TAG: Symbol = Symbol("tag");
 pub const DEFAULT: Symbol = Symbol("default");
+pub const READONLY: Symbol = Symbol("readonly");
+pub const DECL: Symbol = Symbol("decl");
+pub const INTERNAL: Symbol = Symbol("internal");
+pub const EXPORT: Symbol = Symbol("export");
 
 impl PartialEq<Symbol> for Ident {
     fn eq(&self, other: &Symbol) -> bool {
@@ -0,0 +1,36 @@
+use crate::descriptor::DescriptorManager;
+
+/// A generator of the files in a target language.
+///
+/// It receives the descriptors of a `FileGroup`, whose file names are already
+/// resolved, and returns the paths relative to the output directory with
+/// their contents. The types are found by `declared_types` or `declared_files`,
+/// and `type_key`, `fields`, `tag`, `generic_params` and `dependencies` of the
+/// `DescriptorManager` describe them. The APIs are in `apis`, whose types are
+/// found by `api_dependencies`.
+///
+/// ```ignore
+/// struct Names;
+///
+/// impl Backend for Names {
+///     fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+///         let names = manager
+///             .declared_types()
+///             .into_iter()
+///             .map(|idx| manager.type_key(idx).1)
+///             .collect::<Vec<_>>();
+///         vec![(String::from("names.txt"), names.join("\n"))]
+///     }
+/// }
+///
+/// group.gen_with(&Names, "outdir");
+/// ```
+pub trait Backend {
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)>;
+
+    /// The extensions of the generated files, like `ts`. The stale files with
+    /// them are pruned even if no file is generated this time.
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+}
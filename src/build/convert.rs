@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    Casing, Config, Descriptor, DescriptorManager, EnumDescriptor, FieldDescriptor, FileNaming,
+    InterfaceDescriptor, TypeExpr,
+};
+use syn::{GenericArgument, PathArguments, Type};
+
+use super::source::{absolute_path, SourceModule};
+use gents_derives_internals::container::{Container, Decl, RenameAll};
+
+// A type deriving `TS` found in the sources.
+struct TypeEntry {
+    idx: usize,
+    ts_name: String,
+    file_name: String,
+}
+
+/// Build the descriptors of the types selected by `select` and the types they
+/// depend on. The `rename_all` of the config is applied to the types without one.
+///
+/// Unlike the `TS` trait, generic types are described by their parameters
+/// directly, so they need not be instantiated.
+///
+/// The warnings about the types neither deriving `TS` nor builtin are returned
+/// along with the descriptors.
+pub fn build_manager<F>(
+    modules: &[SourceModule],
+    config: &Config,
+    select: F,
+) -> (DescriptorManager, Vec<String>)
+where
+    F: Fn(&SourceModule, &Container) -> bool,
+{
+    let rename_all = &config.rename_all.map(|c| match c {
+        Casing::CamelCase => RenameAll::CamelCase,
+    });
+    let containers = modules
+        .iter()
+        .flat_map(|m| {
+            m.items.iter().map(move |i| {
+                let mut c = Container::from_ast(i);
+                if c.rename_all.is_none() {
+                    c.rename_all = rename_all.clone();
+                }
+                (m, c)
+            })
+        })
+        .collect::<Vec<_>>();
+    // Types are identified by their full paths like `crate::models::User`.
+    let table = containers
+        .iter()
+        .enumerate()
+        .map(|(idx, (m, c))| {
+            let ts_name = c.ts_name();
+            let file_name = match &c.file_name {
+                Some(f) => f.to_string(),
+                None => FileNaming::Module.file_name(&m.path, &ts_name),
+            };
+            let entry = TypeEntry {
+                idx,
+                ts_name,
+                file_name,
+            };
+            (format!("{}::{}", m.path, c.ident), entry)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut warnings = vec![];
+    let descriptors = containers
+        .iter()
+        .map(|(m, c)| {
+            let entry = &table[&format!("{}::{}", m.path, c.ident)];
+            let mut resolver = Resolver {
+                module: m,
+                generics: c.generics.iter().map(|g| g.to_string()).collect(),
+                table: &table,
+                external: &config.imports,
+                dependencies: vec![],
+                warnings: &mut warnings,
+            };
+            let fields = c
+                .fields
+                .iter()
+                .filter(|f| !f.skip)
+                .map(|f| {
+                    let ty = f.ty.map(|t| resolver.type_expr(t));
+                    FieldDescriptor {
+                        ident: f.ts_name(&c.rename_all, c.is_enum),
+                        optional: matches!(ty, Some(TypeExpr::Option(_))),
+                        ts_ty: ty.as_ref().map(|t| t.ts_name()).unwrap_or_default(),
+                        ty,
+                        readonly: c.readonly || f.readonly,
+                        comments: f.comments.clone(),
+                        tag_value: f.tag_value.clone().unwrap_or_default(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            let module_path = match &c.file_name {
+                Some(_) => None,
+                None => Some(m.path.to_string()),
+            };
+            let descriptor = if c.is_enum {
+                Descriptor::Enum(EnumDescriptor {
+                    dependencies: resolver.dependencies,
+                    fields,
+                    file_name: entry.file_name.to_string(),
+                    ts_name: entry.ts_name.to_string(),
+                    comments: c.comments.clone(),
+                    tag: c.tag.clone().unwrap_or_default(),
+                    generic: None,
+                    internal: c.internal,
+                    module_path,
+                })
+            } else {
+                Descriptor::Interface(InterfaceDescriptor {
+                    dependencies: resolver.dependencies,
+                    fields,
+                    file_name: entry.file_name.to_string(),
+                    ts_name: entry.ts_name.to_string(),
+                    comments: c.comments.clone(),
+                    need_builder: c.need_builder,
+                    generic: None,
+                    decl: c.decl.as_ref().map(|d| match d {
+                        Decl::Interface => crate::DeclKind::Interface,
+                        Decl::TypeAlias => crate::DeclKind::TypeAlias,
+                    }),
+                    internal: c.internal,
+                    module_path,
+                })
+            };
+            (
+                descriptor,
+                c.generics.iter().map(|g| g.to_string()).collect(),
+            )
+        })
+        .collect::<Vec<(Descriptor, Vec<String>)>>();
+
+    let roots = containers
+        .iter()
+        .enumerate()
+        .filter(|(_, (m, c))| select(m, c))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    let kept = reachable(&descriptors, roots);
+    let new_idx = kept
+        .iter()
+        .enumerate()
+        .map(|(new, old)| (*old, new))
+        .collect::<HashMap<_, _>>();
+    let mut manager = DescriptorManager::default();
+    descriptors
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| kept.contains(idx))
+        .for_each(|(idx, (mut descriptor, generics))| {
+            let dependencies = match &mut descriptor {
+                Descriptor::Interface(d) => &mut d.dependencies,
+                Descriptor::Enum(d) => &mut d.dependencies,
+                _ => unreachable!(),
+            };
+            dependencies.iter_mut().for_each(|d| *d = new_idx[d]);
+            manager.descriptors.push(descriptor);
+            if !generics.is_empty() {
+                manager.add_generics_map(new_idx[&idx], generics.join(", "));
+            }
+        });
+    (manager, warnings)
+}
+
+fn reachable(descriptors: &[(Descriptor, Vec<String>)], roots: Vec<usize>) -> BTreeSet<usize> {
+    let mut result = BTreeSet::new();
+    let mut stack = roots;
+    while let Some(idx) = stack.pop() {
+        if !result.insert(idx) {
+            continue;
+        }
+        match &descriptors[idx].0 {
+            Descriptor::Interface(d) => stack.extend(d.dependencies.iter().copied()),
+            Descriptor::Enum(d) => stack.extend(d.dependencies.iter().copied()),
+            _ => {}
+        }
+    }
+    result
+}
+
+struct Resolver<'a> {
+    module: &'a SourceModule,
+    generics: Vec<String>,
+    table: &'a HashMap<String, TypeEntry>,
+    // The types imported from other packages, which are known.
+    external: &'a BTreeMap<String, String>,
+    // The indices of the types deriving `TS` used by the fields.
+    dependencies: Vec<usize>,
+    warnings: &'a mut Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn type_expr(&mut self, ty: &Type) -> TypeExpr {
+        match ty {
+            Type::Reference(r) => self.type_expr(&r.elem),
+            Type::Paren(p) => self.type_expr(&p.elem),
+            Type::Group(g) => self.type_expr(&g.elem),
+            Type::Array(a) => TypeExpr::Array(Box::new(self.type_expr(&a.elem))),
+            Type::Slice(s) => TypeExpr::Array(Box::new(self.type_expr(&s.elem))),
+            Type::Tuple(t) => TypeExpr::Tuple(t.elems.iter().map(|e| self.type_expr(e)).collect()),
+            Type::Path(p) if p.qself.is_none() => self.path_type_expr(&p.path),
+            _ => self.unknown(&quote::quote!(#ty).to_string()),
+        }
+    }
+
+    fn path_type_expr(&mut self, path: &syn::Path) -> TypeExpr {
+        let last = path.segments.last().unwrap();
+        let name = last.ident.to_string();
+        let args = match &last.arguments {
+            PathArguments::AngleBracketed(a) => a
+                .args
+                .iter()
+                .filter_map(|a| match a {
+                    GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            _ => vec![],
+        };
+        if path.segments.len() == 1 && self.generics.contains(&name) {
+            return TypeExpr::Param(name);
+        }
+        if let Some(expr) = self.builtin_type_expr(&name, &args) {
+            return expr;
+        }
+        let segments = path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect::<Vec<_>>();
+        match self.resolve(&segments) {
+            Some(entry) => {
+                self.dependencies.push(entry.idx);
+                TypeExpr::Named {
+                    name: entry.ts_name.to_string(),
+                    file_name: entry.file_name.to_string(),
+                    args: args.into_iter().map(|a| self.type_expr(a)).collect(),
+                }
+            }
+            None if self.external.contains_key(&name) => TypeExpr::Raw(name),
+            None => self.unknown(&name),
+        }
+    }
+
+    fn builtin_type_expr(&mut self, name: &str, args: &[&Type]) -> Option<TypeExpr> {
+        let expr = match (name, args) {
+            ("u8", []) => TypeExpr::Primitive("u8"),
+            ("u16", []) => TypeExpr::Primitive("u16"),
+            ("u32", []) => TypeExpr::Primitive("u32"),
+            ("u64", []) => TypeExpr::Primitive("u64"),
+            ("usize", []) => TypeExpr::Primitive("usize"),
+            ("i8", []) => TypeExpr::Primitive("i8"),
+            ("i16", []) => TypeExpr::Primitive("i16"),
+            ("i32", []) => TypeExpr::Primitive("i32"),
+            ("i64", []) => TypeExpr::Primitive("i64"),
+            ("isize", []) => TypeExpr::Primitive("isize"),
+            ("f32", []) => TypeExpr::Primitive("f32"),
+            ("f64", []) => TypeExpr::Primitive("f64"),
+            ("String", []) | ("str", []) => TypeExpr::Primitive("string"),
+            ("bool", []) => TypeExpr::Primitive("bool"),
+            ("Box", [t]) | ("Rc", [t]) | ("Arc", [t]) => self.type_expr(t),
+            ("Vec", [t]) | ("VecDeque", [t]) | ("HashSet", [t]) | ("BTreeSet", [t]) => {
+                TypeExpr::Array(Box::new(self.type_expr(t)))
+            }
+            ("Option", [t]) => TypeExpr::Option(Box::new(self.type_expr(t))),
+            ("Result", [t, e]) => {
+                TypeExpr::Result(Box::new(self.type_expr(t)), Box::new(self.type_expr(e)))
+            }
+            ("HashMap", [k, v]) | ("BTreeMap", [k, v]) => {
+                TypeExpr::Map(Box::new(self.type_expr(k)), Box::new(self.type_expr(v)))
+            }
+            _ => return None,
+        };
+        Some(expr)
+    }
+
+    // Find the type by the path written in the current module.
+    fn resolve(&self, segments: &[String]) -> Option<&'a TypeEntry> {
+        let module = &self.module.path;
+        let path = match self.module.uses.get(&segments[0]) {
+            Some(used) => {
+                let mut path = vec![used.to_string()];
+                path.extend(segments[1..].iter().cloned());
+                path.join("::")
+            }
+            None => absolute_path(module, segments),
+        };
+        self.table
+            .get(&path)
+            .or_else(|| self.table.get(&format!("{}::{}", module, path)))
+            .or_else(|| self.table.get(&format!("crate::{}", path)))
+    }
+
+    // Types neither deriving `TS` nor builtin are generated as they are written.
+    fn unknown(&mut self, name: &str) -> TypeExpr {
+        self.warnings.push(format!(
+            "unknown type `{}` in `{}`, generated as it is",
+            name, self.module.path
+        ));
+        TypeExpr::Raw(name.to_string())
+    }
+}
@@ -0,0 +1,167 @@
+//! Generate the files from the crate sources, without compiling the crate.
+//!
+//! It is used by the `gents` command and the build scripts. In `build.rs`:
+//! ```ignore
+//! fn main() {
+//!     let mut build = gents::build::Build::new();
+//!     build.set_out_dir(&std::env::var("OUT_DIR").unwrap());
+//!     build.run();
+//! }
+//! ```
+//! and then the generated files can be found by:
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/gents_files.rs"));
+//! // GENTS_FILES: &[&str]
+//! ```
+//!
+//! The types deriving `TS` are found by parsing the sources with `syn` and
+//! their attributes are handled the same way as `gents_derives`. Since
+//! `#[ts(export)]` is not needed when parsing the sources, a group without
+//! tags contains all the types deriving `TS`. Types not selected by the group
+//! are still generated if they are used by the selected ones.
+//!
+//! APIs defined by `#[ts_interface]` are not supported yet.
+
+mod convert;
+mod source;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, GroupConfig};
+use crate::file_generator::FileGroup;
+use gents_derives_internals::container::Container;
+use source::SourceModule;
+
+const MANIFEST_FILE: &str = "gents_files.rs";
+
+/// Generate the groups in `gents.toml` from `build.rs`.
+pub struct Build {
+    config: Config,
+    out_dir: Option<PathBuf>,
+}
+
+/// The files read and written by a build.
+pub struct BuildOutput {
+    pub generated_files: Vec<PathBuf>,
+    // The config file and the source files.
+    pub sources: Vec<PathBuf>,
+    // The types generated as they are written, which are likely mistakes.
+    pub warnings: Vec<String>,
+}
+
+impl Build {
+    /// Use the `gents.toml` in the crate root.
+    pub fn new() -> Self {
+        Build::from_config(Config::discover())
+    }
+
+    pub fn from_config(config: Config) -> Self {
+        Build {
+            config,
+            out_dir: None,
+        }
+    }
+
+    /// Generate the groups into `{dir}/{group name}` instead of their `out_dir`,
+    /// e.g. the `OUT_DIR` of cargo.
+    pub fn set_out_dir(&mut self, dir: &str) {
+        self.out_dir = Some(PathBuf::from(dir));
+    }
+
+    /// Generate the files and print the cargo instructions to rerun the build
+    /// script when the sources change. The list of the generated files is
+    /// written to `$OUT_DIR/gents_files.rs` if run by cargo.
+    pub fn run(self) -> BuildOutput {
+        let output = generate(&self.config, self.out_dir.as_deref());
+        output
+            .sources
+            .iter()
+            .for_each(|f| println!("cargo:rerun-if-changed={}", f.display()));
+        output
+            .warnings
+            .iter()
+            .for_each(|w| println!("cargo:warning=gents: {}", w));
+        if let Ok(dir) = std::env::var("OUT_DIR") {
+            let manifest = Path::new(&dir).join(MANIFEST_FILE);
+            fs::write(&manifest, output.manifest())
+                .unwrap_or_else(|e| panic!("failed to write {}: {}", manifest.display(), e));
+        }
+        output
+    }
+}
+
+impl Default for Build {
+    fn default() -> Self {
+        Build::new()
+    }
+}
+
+impl BuildOutput {
+    /// The Rust code defining `GENTS_FILES`, the paths of the generated files.
+    pub fn manifest(&self) -> String {
+        let files = self
+            .generated_files
+            .iter()
+            .map(|f| format!("    {:?},\n", f.to_string_lossy()))
+            .collect::<String>();
+        format!("pub const GENTS_FILES: &[&str] = &[\n{}];\n", files)
+    }
+}
+
+/// Generate all the groups in the config. The groups are placed in
+/// `{out_dir}/{group name}` if `out_dir` is set.
+pub fn generate(config: &Config, out_dir: Option<&Path>) -> BuildOutput {
+    let src = config.dir.join(config.src.as_deref().unwrap_or("src"));
+    let modules = source::parse_crate(&src);
+    // The groups sharing the types report the same warnings.
+    let mut warnings = BTreeSet::new();
+    let generated_files = config
+        .groups
+        .iter()
+        .flat_map(|group| {
+            let (manager, group_warnings) =
+                convert::build_manager(&modules, config, |m, c| selected(group, m, c));
+            warnings.extend(group_warnings);
+            let mut file_group = FileGroup::from_manager(manager);
+            file_group.apply_config(config, group);
+            match out_dir {
+                Some(dir) => file_group.gen_files(dir.join(&group.name), group.index_file),
+                None => file_group.generate(),
+            }
+        })
+        .collect();
+    let mut sources = modules
+        .iter()
+        .map(|m| m.file.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    sources.insert(0, config.file.clone());
+    BuildOutput {
+        generated_files,
+        sources,
+        warnings: warnings.into_iter().collect(),
+    }
+}
+
+fn selected(group: &GroupConfig, module: &SourceModule, container: &Container) -> bool {
+    let tagged = group.tags.is_empty()
+        || container
+            .export
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|t| group.tags.contains(t)));
+    // The module paths of the sources start with `crate` instead of the crate name.
+    let in_module = match &group.module_prefix {
+        Some(prefix) => {
+            let prefix = match prefix.split_once("::") {
+                Some((_, rest)) => format!("crate::{}", rest),
+                None => String::from("crate"),
+            };
+            module.path == prefix || module.path.starts_with(&format!("{}::", prefix))
+        }
+        None => true,
+    };
+    tagged && in_module
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Attribute, DeriveInput, Item, UseTree};
+
+/// A Rust module and the types deriving `TS` in it.
+pub struct SourceModule {
+    // Like `crate::models::user`.
+    pub path: String,
+    // The file defining this module.
+    pub file: PathBuf,
+    // The names brought into scope by `use`, mapped to their paths.
+    pub uses: HashMap<String, String>,
+    pub items: Vec<DeriveInput>,
+}
+
+/// Parse the modules from `lib.rs` or `main.rs` in the directory.
+pub fn parse_crate(src_dir: &Path) -> Vec<SourceModule> {
+    let root = ["lib.rs", "main.rs"]
+        .iter()
+        .map(|f| src_dir.join(f))
+        .find(|f| f.exists())
+        .unwrap_or_else(|| panic!("lib.rs or main.rs not found in {}", src_dir.display()));
+    let mut result = vec![];
+    parse_file(
+        &root,
+        String::from("crate"),
+        src_dir.to_path_buf(),
+        &mut result,
+    );
+    result
+}
+
+// `dir` is where to find the files of the sub-modules.
+fn parse_file(file: &Path, module_path: String, dir: PathBuf, result: &mut Vec<SourceModule>) {
+    let content = fs::read_to_string(file)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", file.display(), e));
+    let ast = syn::parse_file(&content)
+        .unwrap_or_else(|e| panic!("failed to parse {}: {}", file.display(), e));
+    parse_items(&ast.items, module_path, file, dir, result);
+}
+
+fn parse_items(
+    items: &[Item],
+    module_path: String,
+    file: &Path,
+    dir: PathBuf,
+    result: &mut Vec<SourceModule>,
+) {
+    let mut module = SourceModule {
+        path: module_path,
+        file: file.to_path_buf(),
+        uses: HashMap::new(),
+        items: vec![],
+    };
+    let mut sub_modules = vec![];
+    for item in items {
+        match item {
+            Item::Struct(s) if derives_ts(&s.attrs) => module.items.push(s.clone().into()),
+            Item::Enum(e) if derives_ts(&e.attrs) => module.items.push(e.clone().into()),
+            Item::Use(u) => collect_uses(&u.tree, vec![], &module.path, &mut module.uses),
+            Item::Mod(m) => sub_modules.push(m),
+            _ => {}
+        }
+    }
+    let module_path = module.path.clone();
+    result.push(module);
+    for m in sub_modules {
+        let name = m.ident.to_string();
+        let sub_path = format!("{}::{}", module_path, name);
+        let sub_dir = dir.join(&name);
+        match &m.content {
+            Some((_, items)) => parse_items(items, sub_path, file, sub_dir, result),
+            None => {
+                let file = dir.join(format!("{}.rs", name));
+                if file.exists() {
+                    parse_file(&file, sub_path, sub_dir, result);
+                    continue;
+                }
+                let file = sub_dir.join("mod.rs");
+                if file.exists() {
+                    parse_file(&file, sub_path, sub_dir, result);
+                }
+            }
+        }
+    }
+}
+
+// `#[derive(TS)]` or `#[derive(gents_derives::TS)]`
+fn derives_ts(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .any(|a| {
+            a.parse_args_with(Punctuated::<syn::Path, Comma>::parse_terminated)
+                .map(|paths| {
+                    paths
+                        .iter()
+                        .any(|p| p.segments.last().is_some_and(|s| s.ident == "TS"))
+                })
+                .unwrap_or(false)
+        })
+}
+
+fn collect_uses(
+    tree: &UseTree,
+    prefix: Vec<String>,
+    module_path: &str,
+    uses: &mut HashMap<String, String>,
+) {
+    match tree {
+        UseTree::Path(p) => {
+            let mut prefix = prefix;
+            prefix.push(p.ident.to_string());
+            collect_uses(&p.tree, prefix, module_path, uses);
+        }
+        UseTree::Name(n) => {
+            let mut path = prefix;
+            path.push(n.ident.to_string());
+            uses.insert(n.ident.to_string(), absolute_path(module_path, &path));
+        }
+        UseTree::Rename(r) => {
+            let mut path = prefix;
+            path.push(r.ident.to_string());
+            uses.insert(r.rename.to_string(), absolute_path(module_path, &path));
+        }
+        UseTree::Group(g) => g
+            .items
+            .iter()
+            .for_each(|t| collect_uses(t, prefix.clone(), module_path, uses)),
+        UseTree::Glob(_) => {}
+    }
+}
+
+/// Resolve the paths starting with `crate`, `self` or `super`. Other paths
+/// are kept as they are.
+pub fn absolute_path(module_path: &str, path: &[String]) -> String {
+    let mut module = module_path.split("::").collect::<Vec<_>>();
+    let mut rest = path;
+    match path.first().map(|s| s.as_str()) {
+        Some("crate") => return path.join("::"),
+        Some("self") => rest = &path[1..],
+        Some("super") => {
+            while rest.first().map(|s| s.as_str()) == Some("super") {
+                module.pop();
+                rest = &rest[1..];
+            }
+        }
+        _ => return path.join("::"),
+    }
+    let mut result = module.into_iter().map(String::from).collect::<Vec<_>>();
+    result.extend(rest.iter().cloned());
+    result.join("::")
+}
@@ -0,0 +1,169 @@
+use crate::descriptor::ApiDescriptor;
+use crate::naming::LocalNames;
+use crate::openapi::{param_locations, route, ParamIn};
+use crate::ts_formatter::TsFormatter;
+use crate::typescript::api_ts_name;
+
+// The file declaring the requests and the transports of the clients.
+pub(crate) const TRANSPORT_FILE: &str = "transport.ts";
+
+// `UserApi` -> `UserApiClient`
+pub(crate) fn client_name(api: &str) -> String {
+    format!("{}Client", api)
+}
+
+pub(crate) fn write_transport(fmt: &mut TsFormatter) {
+    let decl = fmt.options().decl;
+    fmt.add_comment(&[String::from(
+        "A call of a generated API client, which can be sent by JSON-RPC or REST.",
+    )]);
+    fmt.start_declaration(decl, "ApiRequest", "");
+    let comments = |c: &str| vec![c.to_string()];
+    fmt.add_field(
+        "method",
+        "string",
+        false,
+        false,
+        &comments("The API and the method, like `UserApi.getUser`."),
+    );
+    fmt.add_field(
+        "params",
+        "unknown[]",
+        false,
+        false,
+        &comments("The params in order."),
+    );
+    fmt.add_field(
+        "http",
+        "HttpRequest",
+        false,
+        false,
+        &comments("The same call as a REST request, as described by the OpenAPI document."),
+    );
+    fmt.end_declaration(decl);
+    fmt.add_blank_line();
+    fmt.start_declaration(decl, "HttpRequest", "");
+    fmt.add_field("method", "string", false, false, &[]);
+    fmt.add_field(
+        "path",
+        "string",
+        false,
+        false,
+        &comments("With the path params, which are encoded."),
+    );
+    fmt.add_field("query", "Record<string, unknown>", false, false, &[]);
+    fmt.add_field("body", "unknown", true, false, &[]);
+    fmt.end_declaration(decl);
+    fmt.add_blank_line();
+    fmt.add_type_alias("Transport", "(request: ApiRequest) => unknown");
+    fmt.add_blank_line();
+    fmt.add_type_alias(
+        "AsyncTransport",
+        "(request: ApiRequest) => Promise<unknown>",
+    );
+}
+
+// A class implementing the API by sending the calls through the transport,
+// which returns the decoded JSON of the results.
+pub(crate) fn write_client(api: &ApiDescriptor, fmt: &mut TsFormatter, names: &LocalNames) {
+    let transport = if api.async_func {
+        "AsyncTransport"
+    } else {
+        "Transport"
+    };
+    let heritage = format!("implements {}", api.name);
+    fmt.start_class_with(&client_name(&api.name), &heritage);
+    fmt.start_method(&format!(
+        "constructor(private readonly transport: {})",
+        transport
+    ));
+    fmt.end_method();
+    let integers = fmt.options().integer_types.clone();
+    api.methods.iter().for_each(|m| {
+        fmt.add_blank_line();
+        let params = m
+            .params
+            .iter()
+            .zip(&m.param_types)
+            .map(|((n, _), t)| format!("{}: {}", n, api_ts_name(t, names, &integers)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ret = m
+            .return_type_expr
+            .as_ref()
+            .map(|t| api_ts_name(t, names, &integers));
+        let ret_str = ret.clone().unwrap_or_else(|| String::from("void"));
+        let signature = if api.async_func {
+            format!("async {}({}): Promise<{}>", m.name, params, ret_str)
+        } else {
+            format!("{}({}): {}", m.name, params, ret_str)
+        };
+        fmt.add_comment(&m.comment);
+        fmt.start_method(&signature);
+
+        let (method, path) = route(api, m);
+        let mut path_expr = path.to_string();
+        let mut query = vec![];
+        let mut body = vec![];
+        param_locations(m, &method, &path)
+            .into_iter()
+            .zip(m.params.iter())
+            .for_each(|(location, (name, _))| match location {
+                ParamIn::Path(t) => {
+                    let value = format!("${{encodeURIComponent(String({}))}}", name);
+                    path_expr = path_expr.replace(&format!("{{{}}}", t), &value);
+                }
+                ParamIn::Query => query.push(name.to_string()),
+                ParamIn::Body => body.push(name.to_string()),
+            });
+        let path_expr = if path_expr == path {
+            fmt.quote(&path)
+        } else {
+            format!("`{}`", path_expr)
+        };
+        let query = if query.is_empty() {
+            String::from("{}")
+        } else {
+            format!("{{ {} }}", query.join(", "))
+        };
+        let body = match body.as_slice() {
+            [] => String::new(),
+            [b] => format!(", body: {}", b),
+            _ => format!(", body: {{ {} }}", body.join(", ")),
+        };
+        let args = m
+            .params
+            .iter()
+            .map(|(n, _)| n.to_string())
+            .collect::<Vec<_>>();
+        fmt.start_method("const request: ApiRequest =");
+        let rpc_method = format!("{}.{}", api.name, m.name);
+        fmt.add_list_item(&format!("method: {}", fmt.quote(&rpc_method)), false);
+        fmt.add_list_item(&format!("params: [{}]", args.join(", ")), false);
+        fmt.add_list_item(
+            &format!(
+                "http: {{ method: {}, path: {}, query: {}{} }}",
+                fmt.quote(&method),
+                path_expr,
+                query,
+                body
+            ),
+            true,
+        );
+        fmt.end_value_block("}");
+        let call = if api.async_func {
+            "await this.transport(request)"
+        } else {
+            "this.transport(request)"
+        };
+        match ret {
+            Some(ret) if api.async_func => {
+                fmt.add_method_line(&format!("return ({}) as {}", call, ret))
+            }
+            Some(ret) => fmt.add_method_line(&format!("return {} as {}", call, ret)),
+            None => fmt.add_method_line(call),
+        }
+        fmt.end_method();
+    });
+    fmt.end_class();
+}
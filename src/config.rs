@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::descriptor::DeclKind;
+use crate::naming::{FileNaming, NameCollision};
+use crate::ts_formatter::{FormatOptions, ImportExtension, Indent, LineEnding, QuoteStyle};
+
+const CONFIG_FILE: &str = "gents.toml";
+
+/// The settings in `gents.toml`, which can be changed without touching the
+/// Rust code.
+///
+/// ```toml
+/// rename_all = "camelCase"
+///
+/// [format]
+/// indent = 2
+/// semicolons = true
+///
+/// [integers]
+/// u64 = "bigint"
+///
+/// [imports]
+/// Dayjs = "dayjs"
+///
+/// [[groups]]
+/// name = "frontend"
+/// out_dir = "web/src/types"
+/// index_file = true
+/// tags = ["frontend"]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    pub file: PathBuf,
+    // The directory of the config file. Paths in the config are relative to it.
+    #[serde(skip)]
+    pub dir: PathBuf,
+    // The crate sources for the `gents` command, `src` by default.
+    pub src: Option<String>,
+    // The default `rename_all` of the types without one. It is applied when
+    // compiling, so that the Typescript names match the serde implementations.
+    pub rename_all: Option<Casing>,
+    #[serde(default)]
+    pub format: FormatConfig,
+    // The Typescript types of the integers, like `u64 = "bigint"`.
+    #[serde(default)]
+    pub integers: BTreeMap<String, IntegerType>,
+    // The types not generated by gents and the modules to import them from.
+    #[serde(default)]
+    pub imports: BTreeMap<String, String>,
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+}
+
+/// The fields of `FormatOptions`. Unset fields keep the defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FormatConfig {
+    // The number of spaces or `"tab"`.
+    pub indent: Option<IndentConfig>,
+    pub semicolons: Option<bool>,
+    // `"single"` or `"double"`.
+    pub quote: Option<QuoteStyle>,
+    pub trailing_comma: Option<bool>,
+    // `"lf"` or `"crlf"`.
+    pub line_ending: Option<LineEnding>,
+    pub readonly_fields: Option<bool>,
+    // `"interface"` or `"type"`.
+    pub decl: Option<DeclKind>,
+    pub type_imports: Option<bool>,
+    // `"none"`, `"js"` or `"ts"`.
+    pub import_extension: Option<ImportExtension>,
+    // `"ignore"`, `"error"` or `"alias"`.
+    pub name_collision: Option<NameCollision>,
+    pub type_prefix: Option<String>,
+    pub type_suffix: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IndentConfig {
+    Spaces(usize),
+    Name(IndentName),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndentName {
+    Tab,
+}
+
+/// The casing of the field names, like `rename_all` of serde.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Casing {
+    #[serde(rename = "camelCase")]
+    CamelCase,
+}
+
+/// The Typescript type of an integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegerType {
+    Number,
+    Bigint,
+    String,
+}
+
+impl IntegerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntegerType::Number => "number",
+            IntegerType::Bigint => "bigint",
+            IntegerType::String => "string",
+        }
+    }
+}
+
+/// The settings of a `FileGroup`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub name: String,
+    pub out_dir: String,
+    #[serde(default)]
+    pub index_file: bool,
+    #[serde(default)]
+    pub per_directory_index: bool,
+    #[serde(default)]
+    pub named_exports: bool,
+    // Add the exported types with these tags. All the exported types are
+    // added if empty.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Only add the exported types in this module, like `my_crate::models`.
+    pub module_prefix: Option<String>,
+    // `"module"` or `"type"`.
+    pub file_naming: Option<FileNaming>,
+    #[serde(default)]
+    pub prune: bool,
+    #[serde(default)]
+    pub declaration_files: bool,
+    #[serde(default)]
+    pub zod_schemas: bool,
+    #[serde(default)]
+    pub type_guards: bool,
+    #[serde(default)]
+    pub api_clients: bool,
+}
+
+impl Config {
+    pub fn from_file(path: &Path) -> Self {
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        let mut config: Config = toml::from_str(&content)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e));
+        config.file = path.to_path_buf();
+        config.dir = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+        config
+    }
+
+    /// Find `gents.toml` in the crate root, which is the current directory
+    /// if not running by cargo.
+    pub fn discover() -> Self {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| String::from("."));
+        Config::from_file(&Path::new(&dir).join(CONFIG_FILE))
+    }
+
+    pub fn group(&self, name: &str) -> &GroupConfig {
+        self.groups
+            .iter()
+            .find(|g| g.name == name)
+            .unwrap_or_else(|| panic!("group `{}` not found in the config", name))
+    }
+
+    pub fn format_options(&self) -> FormatOptions {
+        let mut options = FormatOptions::default();
+        let f = &self.format;
+        if let Some(indent) = &f.indent {
+            options.indent = match indent {
+                IndentConfig::Spaces(n) => Indent::Spaces(*n),
+                IndentConfig::Name(IndentName::Tab) => Indent::Tab,
+            };
+        }
+        options.quote = f.quote.unwrap_or(options.quote);
+        options.line_ending = f.line_ending.unwrap_or(options.line_ending);
+        options.decl = f.decl.unwrap_or(options.decl);
+        options.import_extension = f.import_extension.unwrap_or(options.import_extension);
+        options.name_collision = f.name_collision.unwrap_or(options.name_collision);
+        options.semicolons = f.semicolons.unwrap_or(options.semicolons);
+        options.trailing_comma = f.trailing_comma.unwrap_or(options.trailing_comma);
+        options.readonly_fields = f.readonly_fields.unwrap_or(options.readonly_fields);
+        options.type_imports = f.type_imports.unwrap_or(options.type_imports);
+        if let Some(prefix) = &f.type_prefix {
+            options.type_prefix = prefix.to_string();
+        }
+        if let Some(suffix) = &f.type_suffix {
+            options.type_suffix = suffix.to_string();
+        }
+        options.integer_types = self
+            .integers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.as_str().to_string()))
+            .collect();
+        options.external_imports = self.imports.clone();
+        options
+    }
+}
+
+impl GroupConfig {
+    pub fn file_naming(&self) -> FileNaming {
+        self.file_naming.unwrap_or_default()
+    }
+}
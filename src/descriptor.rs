@@ -1,10 +1,15 @@
 use std::{
     any::TypeId,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    rc::Rc,
+    sync::Arc,
 };
 
-use crate::ts_formatter::TsFormatter;
-use crate::utils::remove_ext;
+use serde::Deserialize;
+
+use crate::naming::{FileNaming, LocalNames, TypeKey};
+use crate::ts_formatter::{ts_type_name, FormatOptions};
+use crate::typescript::TypescriptBackend;
 
 // `TS` trait defines the behavior of your types when generating files.
 // `TS` generates some helper functions for file generator.
@@ -26,6 +31,40 @@ pub trait TS: Clone {
     fn _tag() -> Option<&'static str> {
         None
     }
+    // The structure of this type, used when the output depends on more than
+    // the Typescript name, e.g. mapping `Map` to `ReadonlyMap`.
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Raw(Self::_ts_name())
+    }
+}
+
+/// Describe the structure of a type used by a field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpr {
+    // Rust builtin types identified by their tags, like `u8` and `string`.
+    Primitive(&'static str),
+    // The generic parameter like `T`.
+    Param(String),
+    // Types deriving `TS`, identified by their names and the files declaring them.
+    Named {
+        name: String,
+        file_name: String,
+        args: Vec<TypeExpr>,
+    },
+    Array(Box<TypeExpr>),
+    Option(Box<TypeExpr>),
+    Result(Box<TypeExpr>, Box<TypeExpr>),
+    Tuple(Vec<TypeExpr>),
+    Map(Box<TypeExpr>, Box<TypeExpr>),
+    // Types only known by their Typescript names.
+    Raw(String),
+}
+
+impl TypeExpr {
+    /// The name of this type in Typescript, like `readonly User[]`.
+    pub fn ts_name(&self) -> String {
+        ts_type_name(self, false, &LocalNames::new(), &BTreeMap::new())
+    }
 }
 
 /// Trait for defining TypeScript API interfaces
@@ -43,17 +82,43 @@ pub struct ApiDescriptor {
     pub async_func: bool,
 }
 
+/// The `TS::_register` of a type.
+pub type RegisterFn = fn(&mut DescriptorManager, bool) -> usize;
+
 pub struct MethodDescriptor {
     pub name: String,
     pub params: Vec<(String, TypeId)>,
     pub comment: Vec<String>,
     pub return_type: Option<TypeId>,
+    // The types of the params and the return type, in the same order.
+    pub param_types: Vec<TypeExpr>,
+    pub return_type_expr: Option<TypeExpr>,
+    // `TS::_register` of the types of the params and the return type, so that
+    // they are registered with the API.
+    pub param_registers: Vec<RegisterFn>,
+    pub return_register: Option<RegisterFn>,
+    // Like `POST /users`, set by `#[ts(http = "POST /users")]`.
+    pub http: Option<String>,
+}
+
+/// A Tauri command marked with `#[ts_command]`.
+pub struct CommandDescriptor {
+    // The name of the Rust function, which is the name of the command.
+    pub name: String,
+    pub file_name: String,
+    // The names of the args passed to `invoke` and their types. The args
+    // injected by Tauri, like `State`, are skipped.
+    pub params: Vec<(String, TypeExpr)>,
+    // `T` of `Result<T, E>`, since the errors reject the promise.
+    pub return_type: Option<TypeExpr>,
+    pub comment: Vec<String>,
 }
 
 #[derive(Default)]
 pub struct DescriptorManager {
     pub descriptors: Vec<Descriptor>,
     pub api_descriptors: Vec<ApiDescriptor>,
+    pub commands: Vec<CommandDescriptor>,
     pub id_map: HashMap<TypeId, usize>,
     generics_map: HashMap<usize, String>,
 }
@@ -71,172 +136,325 @@ impl DescriptorManager {
         }
     }
 
+    /// Add the API and the types used by its methods.
     pub fn add_api_descriptor(&mut self, descriptor: ApiDescriptor) {
         <&str as TS>::_register(self, false);
+        descriptor
+            .methods
+            .iter()
+            .flat_map(|m| m.param_registers.iter().chain(&m.return_register))
+            .for_each(|register| {
+                register(self, true);
+            });
         self.api_descriptors.push(descriptor);
     }
 
+    // A command may be added by both `add_all` and `add_by_tag`.
+    pub fn add_command(&mut self, descriptor: CommandDescriptor) {
+        if self.commands.iter().all(|c| c.name != descriptor.name) {
+            self.commands.push(descriptor);
+        }
+    }
+
     pub fn add_generics_map(&mut self, idx: usize, generics: String) {
         self.generics_map.insert(idx, generics);
     }
 
+    /// Move the types whose file names are derived from their modules
+    /// according to `naming`.
+    pub fn apply_file_naming(&mut self, naming: FileNaming) {
+        let mut moved = HashMap::new();
+        self.descriptors.iter_mut().for_each(|d| {
+            let (file_name, ts_name, module_path) = match d {
+                Descriptor::Interface(d) => (&mut d.file_name, &d.ts_name, &d.module_path),
+                Descriptor::Enum(e) => (&mut e.file_name, &e.ts_name, &e.module_path),
+                _ => return,
+            };
+            if let Some(module_path) = module_path {
+                let new_file_name = naming.file_name(module_path, ts_name);
+                moved.insert((file_name.clone(), ts_name.clone()), new_file_name.clone());
+                *file_name = new_file_name;
+            }
+        });
+        self.descriptors.iter_mut().for_each(|d| {
+            let fields = match d {
+                Descriptor::Interface(d) => &mut d.fields,
+                Descriptor::Enum(e) => &mut e.fields,
+                _ => return,
+            };
+            fields
+                .iter_mut()
+                .filter_map(|f| f.ty.as_mut())
+                .for_each(|ty| move_type_expr(ty, &moved));
+        });
+        self.api_descriptors
+            .iter_mut()
+            .flat_map(|api| api.methods.iter_mut())
+            .flat_map(|m| m.param_types.iter_mut().chain(m.return_type_expr.as_mut()))
+            .for_each(|ty| move_type_expr(ty, &moved));
+        self.commands
+            .iter_mut()
+            .flat_map(|c| {
+                let params = c.params.iter_mut().map(|(_, ty)| ty);
+                params.chain(c.return_type.as_mut())
+            })
+            .for_each(|ty| move_type_expr(ty, &moved));
+    }
+
     pub fn gen_data(self) -> Vec<(String, String)> {
-        let mut result: Vec<(String, String)> = vec![];
-        let DescriptorManager {
-            descriptors,
-            api_descriptors,
-            id_map,
-            generics_map,
-        } = self;
-        descriptors
+        self.gen_data_with_options(&FormatOptions::default())
+    }
+
+    /// Generate the file names and their contents in the given style.
+    pub fn gen_data_with_options(self, options: &FormatOptions) -> Vec<(String, String)> {
+        let backend = TypescriptBackend {
+            options: options.clone(),
+            ..Default::default()
+        };
+        backend
+            .gen_ts_files(&self)
+            .into_iter()
+            .map(|f| (f.file_name, f.content))
+            .collect()
+    }
+
+    /// The indices of the interfaces and the enums to declare, in the order
+    /// of registration. Instances of the generic types like `Page<User>` are
+    /// declared by their bases.
+    pub fn declared_types(&self) -> Vec<usize> {
+        self.descriptors
             .iter()
             .enumerate()
-            .for_each(|(idx, descriptor)| match &descriptor {
-                Descriptor::Interface(d) => {
-                    if d.generic.is_some() {
-                        return;
-                    }
-                    let generics = if let Some(v) = generics_map.get(&idx) {
-                        format!("<{}>", v).to_string()
-                    } else {
-                        String::new()
-                    };
-                    let import_deps =
-                        d.dependencies
-                            .iter()
-                            .fold(HashSet::new(), |mut prev, curr| {
-                                let deps = get_import_deps_idx(&descriptors, *curr);
-                                prev.extend(deps);
-                                prev
-                            });
-
-                    let mut fmt = TsFormatter::new();
-                    // imports
-                    {
-                        let mut deps: Vec<_> = import_deps.into_iter().collect();
-                        deps.sort();
-                        for dep in deps {
-                            let (ts_name, file_name) = get_import_deps(&descriptors, dep);
-                            fmt.add_import(&ts_name, &file_name);
-                        }
-                    }
-
-                    // comments and interface body
-                    fmt.add_comment(&d.comments);
-                    fmt.start_interface(&d.ts_name, &generics);
-                    for fd in &d.fields {
-                        fmt.add_field(&fd.ident, &fd.ts_ty, fd.optional, &fd.comments);
-                    }
-                    fmt.end_interface();
-
-                    if d.need_builder {
-                        fmt.add_blank_line();
-                        write_builder(&d, &mut fmt);
-                    }
-
-                    result.push((d.file_name.to_string(), fmt.end_file()))
-                }
-                Descriptor::Enum(e) => {
-                    if e.generic.is_some() {
-                        return;
-                    }
-                    let import_deps =
-                        e.dependencies
-                            .iter()
-                            .fold(HashSet::new(), |mut prev, curr| {
-                                let deps = get_import_deps_idx(&descriptors, *curr);
-                                prev.extend(deps);
-                                prev
-                            });
-
-                    let mut fmt = TsFormatter::new();
-                    // imports
-                    {
-                        let mut deps: Vec<_> = import_deps.into_iter().collect();
-                        deps.sort();
-                        for dep in deps {
-                            let (ts_name, file_name) = get_import_deps(&descriptors, dep);
-                            fmt.add_import(&ts_name, &file_name);
-                        }
-                    }
-                    // comments and type union
-                    fmt.add_comment(&e.comments);
-                    fmt.start_enum(&e.ts_name);
-                    for fd in &e.fields {
-                        let ty = fd.ts_ty.to_string();
-                        let v = if ty != "" {
-                            format!("{{ {}: '{}'; value: {} }}", e.tag, fd.tag_value, ty)
-                        } else {
-                            format!(r#"'{}'"#, fd.tag_value)
-                        };
-                        fmt.add_enum_variant_raw(&v);
-                    }
-                    fmt.end_enum();
-
-                    result.push((e.file_name.to_string(), fmt.end_file()))
-                }
-                _ => {}
-            });
-        api_descriptors.into_iter().for_each(|api| {
-            let mut fmt = TsFormatter::new();
-            let mut deps = Vec::<TypeId>::new();
-
-            // collect all non-builtin type dependencies from params and return types
-            api.methods.iter().for_each(|m| {
-                m.params.iter().for_each(|(_, t)| {
-                    deps.push(*t);
-                });
-                if let Some(t) = &m.return_type {
-                    deps.push(*t);
-                }
-            });
-            deps.dedup();
-
-            deps.into_iter().for_each(|t| {
-                let idx = *id_map.get(&t).expect(&format!(
-                    "type id {:?} not found in id_map. Please `add()` it first",
-                    t
-                ));
-                let desc = descriptors.get(idx).unwrap();
-                if let Descriptor::BuiltinType(_) = desc {
-                    return;
-                }
-                let (ts_name, file_name) = get_import_deps(&descriptors, idx);
-                fmt.add_import(&ts_name, &file_name);
-            });
-            let async_func = api.async_func;
-
-            // For API files we currently do not emit comments into the generated TS,
-            // so that the output matches the expected test fixtures exactly.
-            fmt.start_interface(&api.name, "");
-
-            api.methods.into_iter().for_each(|m| {
-                let params = m
-                    .params
-                    .into_iter()
-                    .map(|(n, t)| {
-                        let idx = id_map.get(&t).unwrap();
-                        let desc = descriptors.get(*idx).unwrap();
-                        (n, desc.ts_name().to_string())
-                    })
-                    .collect();
-                let ret = m.return_type.as_ref().map(|t| {
-                    let idx = id_map.get(t).unwrap();
-                    let desc = descriptors.get(*idx).unwrap();
-                    desc.ts_name().to_string()
-                });
-                fmt.add_comment(&m.comment);
-                if async_func {
-                    fmt.add_async_method(&m.name, params, ret);
-                } else {
-                    fmt.add_method(&m.name, params, ret);
-                }
+            .filter(|(_, d)| match d {
+                Descriptor::Interface(d) => d.generic.is_none(),
+                Descriptor::Enum(e) => e.generic.is_none(),
+                _ => false,
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Group the declared types by their files in the order of registration.
+    pub fn declared_files(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut files: Vec<(&str, Vec<usize>)> = vec![];
+        self.declared_types().into_iter().for_each(|idx| {
+            let file_name = match self.descriptors.get(idx).unwrap() {
+                Descriptor::Interface(d) => &d.file_name,
+                Descriptor::Enum(e) => &e.file_name,
+                _ => unreachable!(),
+            };
+            match files.iter_mut().find(|(f, _)| f == file_name) {
+                Some((_, idxs)) => idxs.push(idx),
+                None => files.push((file_name, vec![idx])),
+            }
+        });
+        files
+    }
+
+    /// The file name and the name of an interface or an enum, like
+    /// `("models/user.ts", "User")`.
+    pub fn type_key(&self, idx: usize) -> (&str, &str) {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => (&d.file_name, &d.ts_name),
+            Descriptor::Enum(e) => (&e.file_name, &e.ts_name),
+            _ => panic!("{} is not an interface or an enum", idx),
+        }
+    }
+
+    /// The fields of an interface or the variants of an enum.
+    pub fn fields(&self, idx: usize) -> &[FieldDescriptor] {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => &d.fields,
+            Descriptor::Enum(e) => &e.fields,
+            _ => panic!("{} is not an interface or an enum", idx),
+        }
+    }
+
+    /// The doc comments of an interface or an enum.
+    pub fn comments(&self, idx: usize) -> &[String] {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => &d.comments,
+            Descriptor::Enum(e) => &e.comments,
+            _ => panic!("{} is not an interface or an enum", idx),
+        }
+    }
+
+    /// The tag of an enum, like `type` in `{ type: "guest"; value: number }`,
+    /// or `None` for an interface.
+    pub fn tag(&self, idx: usize) -> Option<&str> {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Enum(e) => Some(&e.tag),
+            _ => None,
+        }
+    }
+
+    /// Whether the type is excluded from the index files.
+    pub fn internal(&self, idx: usize) -> bool {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => d.internal,
+            Descriptor::Enum(e) => e.internal,
+            _ => panic!("{} is not an interface or an enum", idx),
+        }
+    }
+
+    /// The declared types used by an interface or an enum, in which the
+    /// generic types like `Page<User>` are resolved to `Page` and `User`.
+    pub fn dependencies(&self, idx: usize) -> Vec<usize> {
+        let deps = match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => &d.dependencies,
+            Descriptor::Enum(e) => &e.dependencies,
+            _ => panic!("{} is not an interface or an enum", idx),
+        };
+        self.resolve(deps, &self.named_types(idx))
+    }
+
+    /// The APIs added by `add_api`, `add_all` or `add_by_tag`.
+    pub fn apis(&self) -> &[ApiDescriptor] {
+        &self.api_descriptors
+    }
+
+    /// The declared types used by the params and the return types of an API,
+    /// resolved like `dependencies`.
+    pub fn api_dependencies(&self, api: &ApiDescriptor) -> Vec<usize> {
+        let mut types = BTreeSet::new();
+        api.methods
+            .iter()
+            .flat_map(|m| m.param_types.iter().chain(&m.return_type_expr))
+            .for_each(|ty| collect_named_types(ty, &mut types));
+        self.resolve(&self.api_types(api), &types)
+    }
+
+    // The types of the params and the return types of an API.
+    pub(crate) fn api_types(&self, api: &ApiDescriptor) -> Vec<usize> {
+        let mut deps = Vec::<TypeId>::new();
+        api.methods.iter().for_each(|m| {
+            m.params.iter().for_each(|(_, t)| {
+                deps.push(*t);
             });
-            fmt.end_interface();
-            result.push((api.file_name.to_string(), fmt.end_file()));
+            if let Some(t) = &m.return_type {
+                deps.push(*t);
+            }
         });
+        deps.dedup();
+        deps.into_iter()
+            .map(|t| {
+                // `add_api_descriptor` registers every param and return type.
+                self.id_map.get(&t).copied().unwrap_or_else(|| {
+                    unreachable!("type id {:?} is registered by add_api_descriptor", t)
+                })
+            })
+            .collect()
+    }
+
+    /// The Tauri commands added by `add_all` or `add_by_tag`.
+    pub fn tauri_commands(&self) -> &[CommandDescriptor] {
+        &self.commands
+    }
+
+    // The declared types of the descriptors and the named types. The
+    // instances of the generic types are replaced by their bases, and their
+    // arguments are found in the named types.
+    fn resolve(&self, idxs: &[usize], types: &BTreeSet<TypeKey>) -> Vec<usize> {
+        let mut result = BTreeSet::new();
+        idxs.iter()
+            .for_each(|idx| self.resolve_into(*idx, &mut result));
+        self.declared_types()
+            .into_iter()
+            .filter(|idx| {
+                let (file_name, name) = self.type_key(*idx);
+                types.contains(&(file_name.to_string(), name.to_string()))
+            })
+            .for_each(|idx| {
+                result.insert(idx);
+            });
+        result.into_iter().collect()
+    }
+
+    fn resolve_into(&self, idx: usize, result: &mut BTreeSet<usize>) {
+        match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(InterfaceDescriptor {
+                generic: Some(base),
+                ..
+            })
+            | Descriptor::Enum(EnumDescriptor {
+                generic: Some(base),
+                ..
+            }) => {
+                result.insert(*base);
+            }
+            Descriptor::Interface(_) | Descriptor::Enum(_) => {
+                result.insert(idx);
+            }
+            Descriptor::Generics(d) => d
+                .dependencies
+                .iter()
+                .for_each(|dep| self.resolve_into(*dep, result)),
+            Descriptor::BuiltinType(_) => {}
+        }
+    }
+
+    /// The file names and the names of the types used by the fields of an
+    /// interface or an enum, including the arguments of the generic types
+    /// like `User` in `Page<User>`.
+    pub fn named_types(&self, idx: usize) -> BTreeSet<(String, String)> {
+        let fields = match self.descriptors.get(idx).unwrap() {
+            Descriptor::Interface(d) => &d.fields,
+            Descriptor::Enum(e) => &e.fields,
+            _ => unreachable!(),
+        };
+        let mut result = BTreeSet::new();
+        fields
+            .iter()
+            .filter_map(|f| f.ty.as_ref())
+            .for_each(|ty| collect_named_types(ty, &mut result));
         result
     }
+
+    /// The generic parameters of the type, like `["T"]` for `Page<T>`.
+    pub fn generic_params(&self, idx: usize) -> Vec<String> {
+        self.generics_map
+            .get(&idx)
+            .map(|g| g.split(", ").map(String::from).collect())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn collect_named_types(ty: &TypeExpr, result: &mut BTreeSet<TypeKey>) {
+    match ty {
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            result.insert((file_name.to_string(), name.to_string()));
+            args.iter().for_each(|a| collect_named_types(a, result));
+        }
+        TypeExpr::Array(t) | TypeExpr::Option(t) => collect_named_types(t, result),
+        TypeExpr::Result(a, b) | TypeExpr::Map(a, b) => {
+            collect_named_types(a, result);
+            collect_named_types(b, result);
+        }
+        TypeExpr::Tuple(items) => items.iter().for_each(|i| collect_named_types(i, result)),
+        TypeExpr::Primitive(_) | TypeExpr::Param(_) | TypeExpr::Raw(_) => {}
+    }
+}
+
+// The types only known by their names, which may need to be imported from
+// other packages.
+pub(crate) fn collect_raw_names(ty: &TypeExpr, result: &mut BTreeSet<String>) {
+    match ty {
+        TypeExpr::Raw(name) => {
+            result.insert(name.to_string());
+        }
+        TypeExpr::Named { args, .. } => args.iter().for_each(|a| collect_raw_names(a, result)),
+        TypeExpr::Array(t) | TypeExpr::Option(t) => collect_raw_names(t, result),
+        TypeExpr::Result(a, b) | TypeExpr::Map(a, b) => {
+            collect_raw_names(a, result);
+            collect_raw_names(b, result);
+        }
+        TypeExpr::Tuple(items) => items.iter().for_each(|i| collect_raw_names(i, result)),
+        TypeExpr::Primitive(_) | TypeExpr::Param(_) => {}
+    }
 }
 
 // todo: InterfaceDescriptor and EnumDescriptor are the same now.
@@ -249,17 +467,6 @@ pub enum Descriptor {
     Generics(GenericDescriptor),
 }
 
-impl Descriptor {
-    fn ts_name(&self) -> &str {
-        match self {
-            Descriptor::Interface(desc) => &desc.ts_name,
-            Descriptor::Enum(desc) => &desc.ts_name,
-            Descriptor::BuiltinType(desc) => &desc.ts_name,
-            Descriptor::Generics(desc) => &desc.ts_name,
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct GenericDescriptor {
     pub dependencies: Vec<usize>,
@@ -281,6 +488,21 @@ pub struct EnumDescriptor {
     pub comments: Vec<String>,
     pub tag: String,
     pub generic: Option<usize>,
+    // Exclude this type from the index files.
+    pub internal: bool,
+    // The module declaring this type if its file name is derived from it.
+    pub module_path: Option<String>,
+}
+
+/// The way to declare an object type in Typescript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclKind {
+    // export interface A { ... }
+    Interface,
+    // export type A = { ... }
+    #[serde(rename = "type")]
+    TypeAlias,
 }
 
 /// Describe how to generate a ts interface.
@@ -294,6 +516,12 @@ pub struct InterfaceDescriptor {
     pub comments: Vec<String>,
     pub need_builder: bool,
     pub generic: Option<usize>,
+    // Use the group default if not specified.
+    pub decl: Option<DeclKind>,
+    // Exclude this type from the index files.
+    pub internal: bool,
+    // The module declaring this type if its file name is derived from it.
+    pub module_path: Option<String>,
 }
 
 #[derive(Debug)]
@@ -301,6 +529,9 @@ pub struct FieldDescriptor {
     pub ident: String,
     pub optional: bool,
     pub ts_ty: String,
+    // None for the unit variants of enums.
+    pub ty: Option<TypeExpr>,
+    pub readonly: bool,
     pub comments: Vec<String>,
     pub tag_value: String,
 }
@@ -323,6 +554,10 @@ macro_rules! impl_builtin {
             fn _tag() -> Option<&'static str> {
                 Some($t)
             }
+
+            fn _type_expr() -> TypeExpr {
+                TypeExpr::Primitive($t)
+            }
         }
     };
 }
@@ -343,6 +578,10 @@ impl TS for &str {
     fn _tag() -> Option<&'static str> {
         Some("string")
     }
+
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Primitive("string")
+    }
 }
 
 impl_builtin!(u8, "number", "u8");
@@ -351,8 +590,10 @@ impl_builtin!(u32, "number", "u32");
 impl_builtin!(u64, "number", "u64");
 impl_builtin!(usize, "number", "usize");
 impl_builtin!(i8, "number", "i8");
+impl_builtin!(i16, "number", "i16");
 impl_builtin!(i32, "number", "i32");
 impl_builtin!(i64, "number", "i64");
+impl_builtin!(isize, "number", "isize");
 impl_builtin!(f32, "number", "f32");
 impl_builtin!(f64, "number", "f64");
 impl_builtin!(String, "string", "string");
@@ -378,6 +619,10 @@ impl<T: TS + 'static> TS for Vec<T> {
         }
         format!("readonly {}[]", T::_ts_name())
     }
+
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Array(Box::new(T::_type_expr()))
+    }
 }
 
 impl<T: TS + 'static> TS for Option<T> {
@@ -399,6 +644,10 @@ impl<T: TS + 'static> TS for Option<T> {
     fn _is_optional() -> bool {
         true
     }
+
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Option(Box::new(T::_type_expr()))
+    }
 }
 
 impl<T: TS + 'static, E: TS + 'static> TS for Result<T, E> {
@@ -417,6 +666,10 @@ impl<T: TS + 'static, E: TS + 'static> TS for Result<T, E> {
     fn _ts_name() -> String {
         format!("{} | {}", T::_ts_name(), E::_ts_name())
     }
+
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Result(Box::new(T::_type_expr()), Box::new(E::_type_expr()))
+    }
 }
 
 impl<K, V> TS for (K, V)
@@ -439,97 +692,129 @@ where
     fn _ts_name() -> String {
         format!("(readonly [{}, {}])", K::_ts_name(), V::_ts_name())
     }
-}
-
-impl<K, V> TS for HashMap<K, V>
-where
-    K: TS + 'static,
-    V: TS + 'static,
-{
-    fn _register(manager: &mut DescriptorManager, generic_base: bool) -> usize {
-        let k_dep = K::_register(manager, generic_base);
-        let v_dep = V::_register(manager, generic_base);
-        let descriptor = GenericDescriptor {
-            dependencies: vec![k_dep, v_dep],
-            ts_name: Self::_ts_name(),
-            optional: false,
-        };
-        let type_id = TypeId::of::<Self>();
-        manager.registry(type_id, Descriptor::Generics(descriptor))
-    }
 
-    fn _ts_name() -> String {
-        format!("Map<{}, {}>", K::_ts_name(), V::_ts_name())
+    fn _type_expr() -> TypeExpr {
+        TypeExpr::Tuple(vec![K::_type_expr(), V::_type_expr()])
     }
 }
 
-fn get_import_deps_idx(all: &Vec<Descriptor>, idx: usize) -> HashSet<usize> {
-    let mut result = HashSet::new();
-    let descriptor = all.get(idx).unwrap();
-    match descriptor {
-        Descriptor::Interface(_) => {
-            result.insert(idx);
+// The collections serialized as arrays.
+macro_rules! impl_sequence {
+    ($t: ident) => {
+        impl<T: TS + 'static> TS for $t<T> {
+            fn _register(manager: &mut DescriptorManager, generic_base: bool) -> usize {
+                let idx = T::_register(manager, generic_base);
+                let type_id = TypeId::of::<Self>();
+                let descriptor = GenericDescriptor {
+                    dependencies: vec![idx],
+                    ts_name: Self::_ts_name(),
+                    optional: false,
+                };
+                manager.registry(type_id, Descriptor::Generics(descriptor))
+            }
+
+            fn _ts_name() -> String {
+                format!("readonly {}[]", T::_ts_name())
+            }
+
+            fn _type_expr() -> TypeExpr {
+                TypeExpr::Array(Box::new(T::_type_expr()))
+            }
         }
-        Descriptor::Enum(_) => {
-            result.insert(idx);
+    };
+}
+
+impl_sequence!(VecDeque);
+impl_sequence!(HashSet);
+impl_sequence!(BTreeSet);
+
+macro_rules! impl_map {
+    ($t: ident) => {
+        impl<K, V> TS for $t<K, V>
+        where
+            K: TS + 'static,
+            V: TS + 'static,
+        {
+            fn _register(manager: &mut DescriptorManager, generic_base: bool) -> usize {
+                let k_dep = K::_register(manager, generic_base);
+                let v_dep = V::_register(manager, generic_base);
+                let descriptor = GenericDescriptor {
+                    dependencies: vec![k_dep, v_dep],
+                    ts_name: Self::_ts_name(),
+                    optional: false,
+                };
+                let type_id = TypeId::of::<Self>();
+                manager.registry(type_id, Descriptor::Generics(descriptor))
+            }
+
+            fn _ts_name() -> String {
+                format!("Map<{}, {}>", K::_ts_name(), V::_ts_name())
+            }
+
+            fn _type_expr() -> TypeExpr {
+                TypeExpr::Map(Box::new(K::_type_expr()), Box::new(V::_type_expr()))
+            }
         }
-        Descriptor::BuiltinType(_) => {}
-        Descriptor::Generics(d) => d.dependencies.iter().for_each(|dep| {
-            let deps = get_import_deps_idx(all, *dep);
-            result.extend(deps);
-        }),
     };
-    result
 }
 
-fn get_import_deps(all: &Vec<Descriptor>, idx: usize) -> (String, String) {
-    let descriptor = all.get(idx).unwrap();
-    match descriptor {
-        Descriptor::Interface(d) => (d.ts_name.to_string(), remove_ext(&d.file_name)),
-        Descriptor::Enum(d) => (d.ts_name.to_string(), remove_ext(&d.file_name)),
-        _ => unreachable!(),
-    }
+impl_map!(HashMap);
+impl_map!(BTreeMap);
+
+// The pointers are serialized as the values they point to.
+macro_rules! impl_pointer {
+    ($t: ident) => {
+        impl<T: TS + 'static> TS for $t<T> {
+            // Map the pointer to the descriptor of `T`, so that an API
+            // method taking or returning it can still find its type.
+            fn _register(manager: &mut DescriptorManager, generic_base: bool) -> usize {
+                let idx = T::_register(manager, generic_base);
+                manager.id_map.insert(TypeId::of::<Self>(), idx);
+                idx
+            }
+
+            fn _ts_name() -> String {
+                T::_ts_name()
+            }
+
+            fn _is_optional() -> bool {
+                T::_is_optional()
+            }
+
+            fn _tag() -> Option<&'static str> {
+                T::_tag()
+            }
+
+            fn _type_expr() -> TypeExpr {
+                T::_type_expr()
+            }
+        }
+    };
 }
 
-fn write_builder(d: &InterfaceDescriptor, fmt: &mut TsFormatter) {
-    // class header
-    fmt.start_class(&format!("{}Builder", d.ts_name));
-    // fields
-    for fd in &d.fields {
-        if fd.optional {
-            fmt.add_class_field(&format!("private _{}?: {}", fd.ident, fd.ts_ty));
-        } else {
-            fmt.add_class_field(&format!("private _{}!: {}", fd.ident, fd.ts_ty));
+impl_pointer!(Box);
+impl_pointer!(Rc);
+impl_pointer!(Arc);
+
+// Update the file names of the types moved by `apply_file_naming`.
+fn move_type_expr(ty: &mut TypeExpr, moved: &HashMap<TypeKey, String>) {
+    match ty {
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            if let Some(f) = moved.get(&(file_name.to_string(), name.to_string())) {
+                *file_name = f.to_string();
+            }
+            args.iter_mut().for_each(|a| move_type_expr(a, moved));
         }
-    }
-    // setters with blank line between when multiple
-    let mut first = true;
-    for fd in &d.fields {
-        if !first {
-            fmt.add_blank_line();
+        TypeExpr::Array(t) | TypeExpr::Option(t) => move_type_expr(t, moved),
+        TypeExpr::Result(a, b) | TypeExpr::Map(a, b) => {
+            move_type_expr(a, moved);
+            move_type_expr(b, moved);
         }
-        first = false;
-        fmt.start_method(&format!("public {}(value: {})", fd.ident, fd.ts_ty));
-        fmt.add_method_line(&format!("this._{} = value", fd.ident));
-        fmt.add_method_line("return this");
-        fmt.end_method();
-    }
-    // build()
-    fmt.start_method("public build()");
-    for fd in d.fields.iter().filter(|fd| !fd.optional) {
-        fmt.add_method_line(&format!(
-            "if (this._{} === undefined) throw new Error('missing {}')",
-            fd.ident, fd.ident
-        ));
-    }
-    let field_set = d
-        .fields
-        .iter()
-        .map(|fd| format!("{}: this._{}", fd.ident, fd.ident))
-        .collect::<Vec<_>>()
-        .join(", ");
-    fmt.add_method_line(&format!("return {{ {} }}", field_set));
-    fmt.end_method();
-    // class end
-    fmt.end_class();
+        TypeExpr::Tuple(items) => items.iter_mut().for_each(|i| move_type_expr(i, moved)),
+        TypeExpr::Primitive(_) | TypeExpr::Param(_) | TypeExpr::Raw(_) => {}
+    }
 }
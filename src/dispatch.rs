@@ -0,0 +1,81 @@
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// The error of a dispatcher generated by `#[ts_interface(dispatch)]`.
+#[derive(Debug)]
+pub enum DispatchError {
+    /// No method of the API has the name.
+    UnknownMethod(String),
+    /// The params are not an array, there are too many of them or one of
+    /// them could not be deserialized.
+    InvalidParams(String),
+    /// The return value could not be serialized.
+    InvalidResult(String),
+    /// The method returned `Err`, which is serialized.
+    Err(Value),
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::UnknownMethod(m) => write!(f, "unknown method: {}", m),
+            DispatchError::InvalidParams(msg) => write!(f, "invalid params: {}", msg),
+            DispatchError::InvalidResult(msg) => write!(f, "invalid result: {}", msg),
+            DispatchError::Err(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {}
+
+// The helpers below are called by the generated dispatchers.
+
+/// `null` is taken as no params.
+#[doc(hidden)]
+pub fn _dispatch_params(
+    method: &str,
+    params: Value,
+    len: usize,
+) -> Result<Vec<Value>, DispatchError> {
+    let params = match params {
+        Value::Array(params) => params,
+        Value::Null => vec![],
+        _ => {
+            let msg = format!("{} expects an array of params", method);
+            return Err(DispatchError::InvalidParams(msg));
+        }
+    };
+    if params.len() > len {
+        let msg = format!("{} expects {} params, got {}", method, len, params.len());
+        return Err(DispatchError::InvalidParams(msg));
+    }
+    Ok(params)
+}
+
+/// The missing params are `null`, so the trailing `Option`s can be left out.
+#[doc(hidden)]
+pub fn _dispatch_param<T: DeserializeOwned>(
+    method: &str,
+    params: &mut [Value],
+    idx: usize,
+    name: &str,
+) -> Result<T, DispatchError> {
+    let value = params.get_mut(idx).map(Value::take).unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| DispatchError::InvalidParams(format!("{}.{}: {}", method, name, e)))
+}
+
+#[doc(hidden)]
+pub fn _dispatch_result<T: Serialize>(value: T) -> Result<Value, DispatchError> {
+    serde_json::to_value(value).map_err(|e| DispatchError::InvalidResult(e.to_string()))
+}
+
+#[doc(hidden)]
+pub fn _dispatch_err<E: Serialize>(err: E) -> DispatchError {
+    match serde_json::to_value(err) {
+        Ok(e) => DispatchError::Err(e),
+        Err(e) => DispatchError::InvalidResult(e.to_string()),
+    }
+}
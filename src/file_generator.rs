@@ -1,27 +1,123 @@
-use std::path::Path;
-use std::{fs, io::Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 use crate::_TsAPI;
+use crate::backend::Backend;
+use crate::config::{Config, GroupConfig};
 use crate::descriptor::{DescriptorManager, TS};
+use crate::json_schema::JsonSchemaBackend;
+use crate::naming::FileNaming;
+use crate::python::PythonBackend;
+use crate::registry::{exported_items, in_module};
+use crate::ts_formatter::{FormatOptions, TsFormatter};
+use crate::typescript::{GeneratedFile, TypescriptBackend};
 use crate::utils::remove_ext;
 
-const PREFIX: &'static str = r#"// DO NOT EDIT. CODE GENERATED BY gents."#;
+pub(crate) const PREFIX: &'static str = r#"// DO NOT EDIT. CODE GENERATED BY gents."#;
+
+// The files generated into a directory when pruning, one path relative to it
+// per line, so that the stale files without the header can be found as well.
+const MANIFEST: &str = ".gents-manifest";
+
+/// Options to control the index files.
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    // Generate an `index.ts` in every directory, which re-exports the files
+    // and the index files of the sub-directories in it.
+    pub per_directory: bool,
+    // Use `export { A, B } from './a'` instead of `export * from './a'`.
+    pub named_exports: bool,
+}
 
 /// Members from a FileGroup will:
 /// - generate the .ts files in the same directory
 /// - share their dependencies
-/// - occur overwriting if some of them share the same file name
+/// - be placed in the same file if they share the same file name
 pub struct FileGroup {
     manager: DescriptorManager,
+    prune: bool,
+    options: FormatOptions,
+    declaration_files: bool,
+    bundle_namespace: Option<String>,
+    index_options: IndexOptions,
+    file_naming: FileNaming,
+    module_prefix: Option<String>,
+    // Set by the config and used by `generate`.
+    out_dir: Option<PathBuf>,
+    index_file: bool,
+    zod_schemas: bool,
+    type_guards: bool,
+    api_clients: bool,
 }
 
 impl FileGroup {
     pub fn new() -> Self {
         FileGroup {
             manager: DescriptorManager::default(),
+            prune: false,
+            options: FormatOptions::default(),
+            declaration_files: false,
+            bundle_namespace: None,
+            index_options: IndexOptions::default(),
+            file_naming: FileNaming::default(),
+            module_prefix: None,
+            out_dir: None,
+            index_file: false,
+            zod_schemas: false,
+            type_guards: false,
+            api_clients: false,
         }
     }
 
+    /// Create a group from the descriptors built without the `TS` trait,
+    /// e.g. by parsing the source files.
+    pub fn from_manager(manager: DescriptorManager) -> Self {
+        FileGroup {
+            manager,
+            ..FileGroup::new()
+        }
+    }
+
+    /// Create the group named `name` in the config and add the exported
+    /// items with its tags, or all of them if it has no tags.
+    ///
+    /// ```ignore
+    /// FileGroup::from_config(&Config::discover(), "frontend").generate();
+    /// ```
+    pub fn from_config(config: &Config, name: &str) -> Self {
+        let group = config.group(name);
+        let mut result = FileGroup::new();
+        result.apply_config(config, group);
+        if group.tags.is_empty() {
+            result.add_all();
+        } else {
+            group.tags.iter().for_each(|t| result.add_by_tag(t));
+        }
+        result
+    }
+
+    /// Apply the format options of the config and the settings of the group.
+    /// No items are added.
+    pub fn apply_config(&mut self, config: &Config, group: &GroupConfig) {
+        self.options = config.format_options();
+        self.index_options = IndexOptions {
+            per_directory: group.per_directory_index,
+            named_exports: group.named_exports,
+        };
+        self.prune = group.prune;
+        self.declaration_files = group.declaration_files;
+        self.file_naming = group.file_naming();
+        self.module_prefix = group.module_prefix.clone();
+        self.out_dir = Some(config.dir.join(&group.out_dir));
+        self.index_file = group.index_file;
+        self.zod_schemas = group.zod_schemas;
+        self.type_guards = group.type_guards;
+        self.api_clients = group.api_clients;
+    }
+
     /// Add a TS member into this FileGroup.
     pub fn add<T: TS>(&mut self) {
         T::_register(&mut self.manager, true);
@@ -32,29 +128,393 @@ impl FileGroup {
         self.manager.add_api_descriptor(d);
     }
 
-    pub fn gen_files(self, dir: &str, index_file: bool) {
-        let mut data = self.manager.gen_data();
-        if index_file {
-            let mut exports: Vec<String> = vec![];
-            data.iter().for_each(|(file_name, _)| {
-                let s = format!(r#"export * from './{}'"#, remove_ext(&file_name));
-                exports.push(s);
-            });
-            exports.sort();
-            exports.push(String::from(""));
-            let content = exports.join("\n");
-            data.push((String::from("index.ts"), content));
-        }
-        data.into_iter().for_each(|(file_name, content)| {
-            let mut file_path = Path::new(dir).to_path_buf();
-            file_path.push(file_name);
-            if let Some(p) = file_path.parent() {
-                fs::create_dir_all(p).expect("create dir failed");
+    /// Add all the types and APIs marked with `#[ts(export)]` or
+    /// `#[ts_interface(export)]`, and the commands marked with `#[ts_command]`,
+    /// in the binary.
+    pub fn add_all(&mut self) {
+        self.add_exported(|_| true);
+    }
+
+    /// Add the exported types and APIs with the tag, like `#[ts(export = "frontend")]`.
+    pub fn add_by_tag(&mut self, tag: &str) {
+        self.add_exported(|tags| tags.contains(&tag));
+    }
+
+    /// Only add the exported items in the module or its sub-modules, like
+    /// `my_crate::models`, when calling `add_all` and `add_by_tag`.
+    pub fn set_module_prefix(&mut self, prefix: &str) {
+        self.module_prefix = Some(prefix.to_string());
+    }
+
+    fn add_exported<F: Fn(&[&str]) -> bool>(&mut self, f: F) {
+        let prefix = self.module_prefix.clone();
+        exported_items()
+            .into_iter()
+            .filter(|e| match &prefix {
+                Some(prefix) => in_module(e.module_path, prefix),
+                None => true,
+            })
+            .filter(|e| f(e.tags))
+            .for_each(|e| (e.register)(&mut self.manager));
+    }
+
+    /// Remove the stale files in the output directory when generating.
+    ///
+    /// A file is considered stale if it is no longer produced by this group,
+    /// e.g. its type was renamed or removed, and it starts with the gents header
+    /// or it was generated by an earlier run with pruning. Those runs record
+    /// their files in `.gents-manifest` of the output directory, since the
+    /// JSON files have no header. Other files are never touched, and neither
+    /// are the files of other languages, like the Kotlin files next to the
+    /// Typescript ones. Symbolic links are not followed.
+    pub fn set_prune(&mut self, prune: bool) {
+        self.prune = prune;
+    }
+
+    /// Set the style of the generated code, such as indentation and quotes.
+    pub fn set_format_options(&mut self, options: FormatOptions) {
+        self.options = options;
+    }
+
+    /// Set how to generate the index files when calling `gen_files`.
+    ///
+    /// Types marked with `#[ts(internal)]` are never exported from the index files.
+    pub fn set_index_options(&mut self, options: IndexOptions) {
+        self.index_options = options;
+    }
+
+    /// Write `.d.ts` files instead of `.ts` files if they contain no runtime
+    /// code like builders.
+    pub fn set_declaration_files(&mut self, declaration_files: bool) {
+        self.declaration_files = declaration_files;
+    }
+
+    /// Emit a `zod` schema after each type when calling `gen_files`, like
+    /// `export const UserSchema = z.object({ .. })`, to validate the data at
+    /// runtime. The files import `z` from `zod`.
+    pub fn set_zod_schemas(&mut self, zod_schemas: bool) {
+        self.zod_schemas = zod_schemas;
+    }
+
+    /// Emit a type guard after each type when calling `gen_files`, like
+    /// `export function isUser(x: unknown): x is User`, to check the data at
+    /// runtime without dependencies.
+    pub fn set_type_guards(&mut self, type_guards: bool) {
+        self.type_guards = type_guards;
+    }
+
+    /// Emit a client class after each API when calling `gen_files`, like
+    /// `UserApiClient implements UserApi`, which sends the calls through a
+    /// transport function declared in `transport.ts`.
+    pub fn set_api_clients(&mut self, api_clients: bool) {
+        self.api_clients = api_clients;
+    }
+
+    /// Wrap the types in `declare namespace {name}` when calling `gen_bundle`.
+    pub fn set_bundle_namespace(&mut self, name: &str) {
+        self.bundle_namespace = Some(name.to_string());
+    }
+
+    /// Set where to place the types without `#[ts(file_name = "...")]`. They
+    /// are placed according to their modules by default.
+    pub fn set_file_naming(&mut self, naming: FileNaming) {
+        self.file_naming = naming;
+    }
+
+    /// Generate all the members into one file without imports between them.
+    ///
+    /// Builders are not generated if the file is a `.d.ts` file or the
+    /// types are wrapped in a namespace, since they are declarations only.
+    pub fn gen_bundle<P: AsRef<Path>>(mut self, path: P) {
+        let path = path.as_ref();
+        self.manager.apply_file_naming(self.file_naming);
+        let declaration =
+            path.to_string_lossy().ends_with(".d.ts") || self.bundle_namespace.is_some();
+        let backend = TypescriptBackend {
+            options: self.options.clone(),
+            ..Default::default()
+        };
+        let namespace = self.bundle_namespace.as_deref();
+        let content = backend.gen_bundle(&self.manager, namespace, declaration);
+        let newline = self.options.line_ending.as_str();
+        write_if_changed(path, &format!("{}{}{}", PREFIX, newline, content));
+    }
+
+    /// Generate a JSON Schema (2020-12) for every type into `dir`, like
+    /// `models/User.schema.json` for `User` in `models/user.ts`.
+    pub fn gen_json_schemas<P: AsRef<Path>>(self, dir: P) -> Vec<PathBuf> {
+        let backend = JsonSchemaBackend {
+            options: self.options.clone(),
+        };
+        self.gen_with(&backend, dir)
+    }
+
+    /// Generate Python modules of `TypedDict`s into `dir`, like `models/user.py`
+    /// for `models/user.ts`.
+    pub fn gen_python_files<P: AsRef<Path>>(self, dir: P) -> Vec<PathBuf> {
+        let backend = PythonBackend {
+            options: self.options.clone(),
+        };
+        self.gen_with(&backend, dir)
+    }
+
+    /// Generate all the types into the `$defs` of one JSON Schema file.
+    pub fn gen_json_schema_bundle<P: AsRef<Path>>(mut self, path: P) {
+        self.manager.apply_file_naming(self.file_naming);
+        let backend = JsonSchemaBackend {
+            options: self.options.clone(),
+        };
+        let content = backend.gen_bundle(&self.manager);
+        write_if_changed(path.as_ref(), &content);
+    }
+
+    /// Generate the files into the `out_dir` of the config.
+    pub fn generate(self) -> Vec<PathBuf> {
+        let dir = self
+            .out_dir
+            .clone()
+            .expect("no out_dir is set, use gen_files instead");
+        let index_file = self.index_file;
+        self.gen_files(dir, index_file)
+    }
+
+    /// Generate the files into `dir`.
+    ///
+    /// Files whose content is not changed will not be rewritten so that their
+    /// modification time is kept and file watchers are not triggered. The paths
+    /// of all the generated files are returned.
+    pub fn gen_files<P: AsRef<Path>>(self, dir: P, index_file: bool) -> Vec<PathBuf> {
+        let backend = TypescriptBackend {
+            options: self.options.clone(),
+            index_file,
+            index_options: self.index_options.clone(),
+            declaration_files: self.declaration_files,
+            zod_schemas: self.zod_schemas,
+            type_guards: self.type_guards,
+            api_clients: self.api_clients,
+        };
+        self.gen_with(&backend, dir)
+    }
+
+    /// Generate the files of another target language into `dir`, in the same
+    /// way as `gen_files`.
+    pub fn gen_with<B: Backend, P: AsRef<Path>>(mut self, backend: &B, dir: P) -> Vec<PathBuf> {
+        let dir = dir.as_ref();
+        self.manager.apply_file_naming(self.file_naming);
+        // Later files overwrite the earlier ones sharing the same name.
+        let files = backend
+            .gen_files(&self.manager)
+            .into_iter()
+            .map(|(file_name, content)| (dir.join(file_name), content))
+            .collect::<BTreeMap<_, _>>();
+        files
+            .iter()
+            .for_each(|(file_path, content)| write_if_changed(file_path, content));
+        if self.prune {
+            let mut extensions = backend
+                .extensions()
+                .iter()
+                .map(OsStr::new)
+                .collect::<BTreeSet<_>>();
+            extensions.extend(files.keys().filter_map(|f| f.extension()));
+            let mut generated = read_manifest(dir);
+            prune_dir(dir, &files, &extensions, &generated);
+            generated.retain(|f| f.exists());
+            generated.extend(files.keys().cloned());
+            write_manifest(dir, &generated);
+        }
+        files.into_keys().collect()
+    }
+}
+
+struct IndexEntry {
+    // The path without extension relative to the directory of the index file.
+    path: String,
+    types: Vec<String>,
+    values: Vec<String>,
+}
+
+pub(crate) fn gen_index_files(
+    files: &[GeneratedFile],
+    options: &FormatOptions,
+    index_options: &IndexOptions,
+) -> Vec<GeneratedFile> {
+    // Later files overwrite the earlier ones sharing the same name.
+    let files = files
+        .iter()
+        .map(|f| (f.file_name.as_str(), f))
+        .collect::<BTreeMap<_, _>>();
+    // directory -> entries
+    let mut dirs: BTreeMap<String, Vec<IndexEntry>> = BTreeMap::new();
+    dirs.insert(String::new(), vec![]);
+    files.into_iter().for_each(|(file_name, f)| {
+        if f.internal {
+            return;
+        }
+        let path = remove_ext(file_name);
+        let (dir, path) = match path.rsplit_once('/') {
+            Some((dir, p)) if index_options.per_directory => (dir.to_string(), p.to_string()),
+            _ => (String::new(), path),
+        };
+        // make sure the parent directories have their index files
+        let mut parent = dir.as_str();
+        while let Some((p, _)) = parent.rsplit_once('/') {
+            dirs.entry(p.to_string()).or_default();
+            parent = p;
+        }
+        dirs.entry(dir).or_default().push(IndexEntry {
+            path,
+            types: f.types.clone(),
+            values: f.values.clone(),
+        });
+    });
+
+    // Deeper directories come first so that their index files can be
+    // exported from their parents.
+    let mut dir_names = dirs.keys().cloned().collect::<Vec<_>>();
+    let depth = |d: &String| {
+        if d.is_empty() {
+            0
+        } else {
+            d.split('/').count()
+        }
+    };
+    dir_names.sort_by_key(|d| std::cmp::Reverse(depth(d)));
+    let mut result = vec![];
+    for dir in dir_names {
+        let mut entries = dirs.remove(&dir).unwrap();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let file_name = if dir.is_empty() {
+            String::from("index.ts")
+        } else {
+            format!("{}/index.ts", dir)
+        };
+        check_duplicated_exports(&file_name, &entries);
+
+        let mut fmt = TsFormatter::new(options);
+        entries.iter().for_each(|e| {
+            if index_options.named_exports {
+                fmt.add_export_names(&e.path, &e.types, &e.values);
+            } else {
+                fmt.add_export_all(&e.path, !e.values.is_empty());
+            }
+        });
+        let types = entries
+            .iter()
+            .flat_map(|e| e.types.clone())
+            .collect::<Vec<_>>();
+        let values = entries
+            .iter()
+            .flat_map(|e| e.values.clone())
+            .collect::<Vec<_>>();
+        if !dir.is_empty() {
+            let (parent, name) = dir.rsplit_once('/').unwrap_or(("", &dir));
+            dirs.entry(parent.to_string())
+                .or_default()
+                .push(IndexEntry {
+                    path: format!("{}/index", name),
+                    types: types.clone(),
+                    values: values.clone(),
+                });
+        }
+        result.push(GeneratedFile {
+            file_name,
+            content: fmt.end_file(),
+            types,
+            values,
+            internal: false,
+        });
+    }
+    result
+}
+
+// The same names exported from different files are ambiguous and will be
+// dropped silently by Typescript.
+fn check_duplicated_exports(index_file: &str, entries: &[IndexEntry]) {
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    let mut duplicates = vec![];
+    entries.iter().for_each(|e| {
+        e.types.iter().chain(e.values.iter()).for_each(|name| {
+            if let Some(prev) = seen.insert(name, &e.path) {
+                duplicates.push(format!("`{}` from './{}' and './{}'", name, prev, e.path));
             }
-            let mut f = fs::File::create(file_path).expect("create file error");
-            f.write_all(PREFIX.as_ref()).expect("write prefix error");
-            f.write("\n".as_ref()).expect("");
-            f.write_all(content.as_ref()).expect("write content error");
         });
+    });
+    if !duplicates.is_empty() {
+        panic!(
+            "duplicated exported names in {}: {}",
+            index_file,
+            duplicates.join(", ")
+        );
+    }
+}
+
+fn write_if_changed(file_path: &Path, content: &str) {
+    if let Ok(existing) = fs::read(file_path) {
+        if existing == content.as_bytes() {
+            return;
+        }
+    }
+    if let Some(p) = file_path.parent() {
+        fs::create_dir_all(p).expect("create dir failed");
+    }
+    fs::write(file_path, content).expect("write file error");
+}
+
+// Only the files with the extensions of the backend are removed, since the
+// backends of other languages share the same header and the manifest.
+fn prune_dir(
+    dir: &Path,
+    files: &BTreeMap<PathBuf, String>,
+    extensions: &BTreeSet<&OsStr>,
+    generated: &BTreeSet<PathBuf>,
+) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries {
+        let entry = entry.expect("read dir entry error");
+        let file_type = entry.file_type().expect("read file type error");
+        let path = entry.path();
+        if file_type.is_dir() {
+            prune_dir(&path, files, extensions, generated);
+            continue;
+        }
+        if !file_type.is_file() || files.contains_key(&path) {
+            continue;
+        }
+        if !path.extension().is_some_and(|e| extensions.contains(e)) {
+            continue;
+        }
+        if generated.contains(&path) || has_prefix(&path) {
+            fs::remove_file(&path).expect("remove stale file error");
+        }
+    }
+}
+
+fn read_manifest(dir: &Path) -> BTreeSet<PathBuf> {
+    fs::read_to_string(dir.join(MANIFEST))
+        .unwrap_or_default()
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| dir.join(l))
+        .collect()
+}
+
+fn write_manifest(dir: &Path, files: &BTreeSet<PathBuf>) {
+    let content = files
+        .iter()
+        .filter_map(|f| f.strip_prefix(dir).ok())
+        .map(|f| format!("{}\n", f.to_string_lossy().replace('\\', "/")))
+        .collect::<String>();
+    write_if_changed(&dir.join(MANIFEST), &content);
+}
+
+fn has_prefix(path: &Path) -> bool {
+    let mut header = vec![0; PREFIX.len()];
+    match fs::File::open(path) {
+        Ok(mut file) => file.read_exact(&mut header).is_ok() && header == PREFIX.as_bytes(),
+        Err(_) => false,
     }
 }
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use crate::descriptor::{EnumDescriptor, InterfaceDescriptor, TypeExpr};
+use crate::naming::LocalNames;
+use crate::ts_formatter::{ts_type_name, TsFormatter};
+
+// `User` -> `isUser`
+pub(crate) fn guard_name(name: &str) -> String {
+    format!("is{}", name)
+}
+
+// `function isPage<T>(x: unknown, isT: (x: unknown) => x is T): x is Page<T>`
+fn signature(name: &str, generics: &[String]) -> String {
+    let params = generics
+        .iter()
+        .map(|g| format!(", {}: (x: unknown) => x is {}", guard_name(g), g))
+        .collect::<String>();
+    let generics = if generics.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", generics.join(", "))
+    };
+    format!(
+        "export function {}{}(x: unknown{}): x is {}{}",
+        guard_name(name),
+        generics,
+        params,
+        name,
+        generics
+    )
+}
+
+pub(crate) fn write_interface_guard(
+    d: &InterfaceDescriptor,
+    name: &str,
+    generics: &[String],
+    fmt: &mut TsFormatter,
+    names: &LocalNames,
+) {
+    let integers = fmt.options().integer_types.clone();
+    fmt.start_function(&guard_name(name), &signature(name, generics));
+    fmt.add_method_line(&not_object(fmt));
+    if !d.fields.is_empty() {
+        fmt.add_method_line("const o = x as Record<string, unknown>");
+    }
+    d.fields.iter().for_each(|fd| {
+        let value = property("o", &fd.ident, fmt);
+        let check = match &fd.ty {
+            Some(ty) => type_check(&value, ty, names, &integers, fmt),
+            None => return,
+        };
+        fmt.add_method_line(&format!("if ({}) return false", negate(&check)));
+    });
+    fmt.add_method_line("return true");
+    fmt.end_method();
+}
+
+// Unit variants are compared with their tag values and the others are
+// checked by their tags and values.
+pub(crate) fn write_enum_guard(
+    e: &EnumDescriptor,
+    name: &str,
+    fmt: &mut TsFormatter,
+    names: &LocalNames,
+) {
+    let integers = fmt.options().integer_types.clone();
+    fmt.start_function(&guard_name(name), &signature(name, &[]));
+    e.fields.iter().filter(|f| f.ty.is_none()).for_each(|fd| {
+        let value = fmt.quote(&fd.tag_value);
+        fmt.add_method_line(&format!("if (x === {}) return true", value));
+    });
+    if e.fields.iter().any(|f| f.ty.is_some()) {
+        fmt.add_method_line(&not_object(fmt));
+        fmt.add_method_line("const o = x as Record<string, unknown>");
+        let tag = property("o", &e.tag, fmt);
+        let value = property("o", "value", fmt);
+        e.fields.iter().for_each(|fd| {
+            if let Some(ty) = &fd.ty {
+                let check = type_check(&value, ty, names, &integers, fmt);
+                let tag_value = fmt.quote(&fd.tag_value);
+                fmt.add_method_line(&format!("if ({} === {}) return {}", tag, tag_value, check));
+            }
+        });
+    }
+    fmt.add_method_line("return false");
+    fmt.end_method();
+}
+
+// `!isA(x)` or `!(typeof x === 'string')`
+fn negate(check: &str) -> String {
+    // Calls like `isA(x)` and the parenthesized expressions.
+    let rest = check.trim_start_matches(|c: char| {
+        c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.'
+    });
+    let mut depth = 0;
+    let atom = rest.is_empty()
+        || rest.starts_with('(')
+            && rest.char_indices().all(|(i, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth > 0 || i == rest.len() - 1
+            });
+    if atom {
+        format!("!{}", check)
+    } else {
+        format!("!({})", check)
+    }
+}
+
+fn not_object(fmt: &TsFormatter) -> String {
+    format!(
+        "if (typeof x !== {} || x === null) return false",
+        fmt.quote("object")
+    )
+}
+
+// `o.name`, or `o['first-name']` if the name is not an identifier.
+fn property(object: &str, name: &str, fmt: &TsFormatter) -> String {
+    let identifier = name.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    });
+    if identifier && !name.is_empty() {
+        format!("{}.{}", object, name)
+    } else {
+        format!("{}[{}]", object, fmt.quote(name))
+    }
+}
+
+/// The expression checking whether `value` is of the type.
+fn type_check(
+    value: &str,
+    ty: &TypeExpr,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    fmt: &TsFormatter,
+) -> String {
+    let check = |v: &str, t: &TypeExpr| type_check(v, t, names, integers, fmt);
+    let type_of = |t: &str| format!("typeof {} === {}", value, fmt.quote(t));
+    match ty {
+        TypeExpr::Primitive(tag) => match *tag {
+            "string" => type_of("string"),
+            "bool" => type_of("boolean"),
+            _ => match integers.get(*tag).map(|s| s.as_str()) {
+                None | Some("number") => type_of("number"),
+                Some(t @ "bigint") | Some(t @ "string") => type_of(t),
+                // Not known by gents.
+                Some(_) => String::from("true"),
+            },
+        },
+        TypeExpr::Param(name) => format!("{}({})", guard_name(name), value),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let local_name = |name: &str, file_name: &str| {
+                let key = (file_name.to_string(), name.to_string());
+                names.get(&key).cloned().unwrap_or_else(|| name.to_string())
+            };
+            // The guards of the arguments are passed to the generic guard.
+            let args = args
+                .iter()
+                .map(|a| match a {
+                    TypeExpr::Named {
+                        name,
+                        file_name,
+                        args,
+                    } if args.is_empty() => guard_name(&local_name(name, file_name)),
+                    TypeExpr::Param(p) => guard_name(p),
+                    _ => {
+                        let ts = ts_type_name(a, false, names, integers);
+                        format!("(v: unknown): v is {} => {}", ts, check("v", a))
+                    }
+                })
+                .map(|a| format!(", {}", a))
+                .collect::<String>();
+            format!(
+                "{}({}{})",
+                guard_name(&local_name(name, file_name)),
+                value,
+                args
+            )
+        }
+        TypeExpr::Array(t) => {
+            if let TypeExpr::Primitive("u8") = t.as_ref() {
+                return format!("{} instanceof Uint8Array", value);
+            }
+            format!(
+                "Array.isArray({}) && {}.every((v) => {})",
+                value,
+                value,
+                check("v", t)
+            )
+        }
+        TypeExpr::Option(t) => format!("({} === undefined || {})", value, check(value, t)),
+        TypeExpr::Result(t, e) => format!("({} || {})", check(value, t), check(value, e)),
+        TypeExpr::Tuple(items) => {
+            let checks = items
+                .iter()
+                .enumerate()
+                .map(|(i, t)| format!(" && {}", check(&format!("{}[{}]", value, i), t)))
+                .collect::<String>();
+            format!(
+                "Array.isArray({}) && {}.length === {}{}",
+                value,
+                value,
+                items.len(),
+                checks
+            )
+        }
+        // The maps are declared as `Map`s, like the ones converted by
+        // serde-wasm-bindgen, so plain objects are rejected.
+        TypeExpr::Map(k, v) => format!(
+            "{} instanceof Map && Array.from({}).every(([k, v]) => {} && {})",
+            value,
+            value,
+            check("k", k),
+            check("v", v)
+        ),
+        // Not known by gents.
+        TypeExpr::Raw(_) => String::from("true"),
+    }
+}
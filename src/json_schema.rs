@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::backend::Backend;
+use crate::descriptor::{Descriptor, DescriptorManager, FieldDescriptor, TypeExpr};
+use crate::naming::{to_pascal, LocalNames, TypeKey, TypeNames};
+use crate::ts_formatter::FormatOptions;
+use crate::utils::{relative_path, remove_ext};
+
+const SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
+const COMMENT: &str = "DO NOT EDIT. CODE GENERATED BY gents.";
+
+// How to refer to the other types.
+pub(crate) enum Refs<'a> {
+    // `#/$defs/User`
+    Defs,
+    // `#/components/schemas/User` in OpenAPI documents.
+    Components,
+    // `./User.schema.json` relative to the current file, with the files of
+    // all the types.
+    Files(String, &'a HashMap<TypeKey, String>),
+}
+
+/// The JSON Schema (2020-12) files generated by `FileGroup::gen_json_schemas`.
+#[derive(Debug, Clone, Default)]
+pub struct JsonSchemaBackend {
+    // Only the names of the types are used.
+    pub options: FormatOptions,
+}
+
+impl Backend for JsonSchemaBackend {
+    // Generate a JSON Schema (2020-12) for every type. The schemas refer to
+    // each other by their relative paths.
+    //
+    // Generic parameters are not checked, since JSON Schema has no generics.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let options = &self.options;
+        let names = TypeNames::new(&manager.descriptors, options);
+        let files = schema_files(manager, &names);
+        manager
+            .descriptors
+            .iter()
+            .filter_map(|d| {
+                let (file_name, ts_name) = schema_type(d)?;
+                let key = (file_name.to_string(), ts_name.to_string());
+                let name = names.get(&key);
+                let schema_file = files[&key].to_string();
+                let refs = Refs::Files(schema_file.to_string(), &files);
+                let mut root = Map::new();
+                root.insert(String::from("$schema"), json!(SCHEMA));
+                root.insert(String::from("$comment"), json!(COMMENT));
+                root.insert(String::from("title"), json!(name));
+                if let Value::Object(schema) = descriptor_schema(d, names.all(), &refs) {
+                    root.extend(schema);
+                }
+                Some((schema_file, to_json(Value::Object(root))))
+            })
+            .collect()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+impl JsonSchemaBackend {
+    /// Generate all the types into the `$defs` of one JSON Schema document.
+    pub(crate) fn gen_bundle(&self, manager: &DescriptorManager) -> String {
+        let options = &self.options;
+        let names = TypeNames::new(&manager.descriptors, options);
+        let defs = manager
+            .descriptors
+            .iter()
+            .filter_map(|d| {
+                let (file_name, ts_name) = schema_type(d)?;
+                let name = &names.global()[&(file_name.to_string(), ts_name.to_string())];
+                let mut schema = Map::new();
+                schema.insert(String::from("title"), json!(name));
+                if let Value::Object(s) = descriptor_schema(d, names.global(), &Refs::Defs) {
+                    schema.extend(s);
+                }
+                Some((name.to_string(), Value::Object(schema)))
+            })
+            .collect::<Map<_, _>>();
+        to_json(json!({
+            "$schema": SCHEMA,
+            "$comment": COMMENT,
+            "$defs": defs,
+        }))
+    }
+}
+
+// The types sharing a name in a directory are prefixed with their files,
+// like `models/UserStatus.schema.json` for `Status` in `models/user.ts`.
+fn schema_files(manager: &DescriptorManager, names: &TypeNames) -> HashMap<TypeKey, String> {
+    let keys = manager
+        .descriptors
+        .iter()
+        .filter_map(schema_type)
+        .map(|(file_name, ts_name)| (file_name.to_string(), ts_name.to_string()))
+        .collect::<Vec<_>>();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    keys.iter().for_each(|k| {
+        *counts
+            .entry(schema_file_name(&k.0, names.get(k)))
+            .or_default() += 1;
+    });
+    keys.into_iter()
+        .map(|k| {
+            let name = names.get(&k);
+            let file = schema_file_name(&k.0, name);
+            if counts[&file] == 1 {
+                return (k, file);
+            }
+            let path = remove_ext(&k.0);
+            let stem = path.rsplit('/').next().unwrap_or(&path);
+            let file = schema_file_name(&k.0, &format!("{}{}", to_pascal(stem), name));
+            (k, file)
+        })
+        .collect()
+}
+
+// The file name and the ts name of the types having schemas. Instances of
+// the generic types are described by their bases.
+pub(crate) fn schema_type(d: &Descriptor) -> Option<(&str, &str)> {
+    match d {
+        Descriptor::Interface(d) if d.generic.is_none() => Some((&d.file_name, &d.ts_name)),
+        Descriptor::Enum(e) if e.generic.is_none() => Some((&e.file_name, &e.ts_name)),
+        _ => None,
+    }
+}
+
+// `models/user.ts` and `User` -> `models/User.schema.json`
+fn schema_file_name(file_name: &str, name: &str) -> String {
+    let file_name = remove_ext(file_name);
+    match file_name.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/{}.schema.json", dir, name),
+        None => format!("{}.schema.json", name),
+    }
+}
+
+pub(crate) fn descriptor_schema(d: &Descriptor, names: &LocalNames, refs: &Refs) -> Value {
+    match d {
+        Descriptor::Interface(d) => {
+            let properties = d
+                .fields
+                .iter()
+                .map(|f| (f.ident.to_string(), field_schema(f, names, refs)))
+                .collect::<Map<_, _>>();
+            let required = d
+                .fields
+                .iter()
+                .filter(|f| !f.optional)
+                .map(|f| json!(f.ident))
+                .collect::<Vec<_>>();
+            with_description(
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                }),
+                &d.comments,
+            )
+        }
+        Descriptor::Enum(e) => {
+            // Unit variants are the tag values and the others are objects
+            // like `{ "type": "guest", "value": 1 }`.
+            let variants = e
+                .fields
+                .iter()
+                .map(|f| {
+                    let variant = match &f.ty {
+                        None => json!({ "const": f.tag_value }),
+                        Some(ty) => json!({
+                            "type": "object",
+                            "properties": {
+                                e.tag.to_string(): { "const": f.tag_value },
+                                "value": type_schema(ty, names, refs),
+                            },
+                            "required": [e.tag, "value"],
+                        }),
+                    };
+                    with_description(variant, &f.comments)
+                })
+                .collect::<Vec<_>>();
+            with_description(json!({ "oneOf": variants }), &e.comments)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn field_schema(f: &FieldDescriptor, names: &LocalNames, refs: &Refs) -> Value {
+    let schema = match &f.ty {
+        // Optional fields are omitted instead of being null.
+        Some(TypeExpr::Option(t)) => type_schema(t, names, refs),
+        Some(ty) => type_schema(ty, names, refs),
+        None => json!({}),
+    };
+    with_description(schema, &f.comments)
+}
+
+pub(crate) fn type_schema(ty: &TypeExpr, names: &LocalNames, refs: &Refs) -> Value {
+    match ty {
+        TypeExpr::Primitive(tag) => match *tag {
+            "string" => json!({ "type": "string" }),
+            "bool" => json!({ "type": "boolean" }),
+            "f32" | "f64" => json!({ "type": "number" }),
+            "u8" | "u16" | "u32" | "u64" | "usize" => json!({ "type": "integer", "minimum": 0 }),
+            _ => json!({ "type": "integer" }),
+        },
+        TypeExpr::Named {
+            name, file_name, ..
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name);
+            let r = match refs {
+                Refs::Defs => format!("#/$defs/{}", name),
+                Refs::Components => format!("#/components/schemas/{}", name),
+                Refs::Files(current, files) => {
+                    let target = match files.get(&key) {
+                        Some(f) => f.to_string(),
+                        None => schema_file_name(file_name, name),
+                    };
+                    let target = target.trim_end_matches(".schema.json");
+                    format!("{}.schema.json", relative_path(current, target))
+                }
+            };
+            json!({ "$ref": r })
+        }
+        TypeExpr::Array(t) => json!({ "type": "array", "items": type_schema(t, names, refs) }),
+        TypeExpr::Option(t) => json!({
+            "anyOf": [type_schema(t, names, refs), { "type": "null" }],
+        }),
+        TypeExpr::Result(t, e) => json!({
+            "anyOf": [type_schema(t, names, refs), type_schema(e, names, refs)],
+        }),
+        TypeExpr::Map(_, v) => json!({
+            "type": "object",
+            "additionalProperties": type_schema(v, names, refs),
+        }),
+        TypeExpr::Tuple(items) => json!({
+            "type": "array",
+            "prefixItems": items.iter().map(|i| type_schema(i, names, refs)).collect::<Vec<_>>(),
+            "items": false,
+            "minItems": items.len(),
+        }),
+        // Generic parameters and the types not known by gents.
+        TypeExpr::Param(_) | TypeExpr::Raw(_) => json!({}),
+    }
+}
+
+pub(crate) fn with_description(mut schema: Value, comments: &[String]) -> Value {
+    if let (Value::Object(s), false) = (&mut schema, comments.is_empty()) {
+        s.insert(String::from("description"), json!(comments.join("\n")));
+    }
+    schema
+}
+
+// The keys of the objects are sorted, so the output is the same whether
+// `preserve_order` of serde_json is enabled by other crates or not.
+pub(crate) fn to_json(mut value: Value) -> String {
+    value.sort_all_objects();
+    let mut s = serde_json::to_string_pretty(&value).unwrap();
+    s.push('\n');
+    s
+}
@@ -0,0 +1,450 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::Backend;
+use crate::descriptor::{Descriptor, DescriptorManager, EnumDescriptor, FieldDescriptor, TypeExpr};
+use crate::file_generator::PREFIX;
+use crate::naming::{to_camel, to_pascal, LocalNames, TypeNames};
+use crate::ts_formatter::FormatOptions;
+use crate::utils::remove_ext;
+
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+// The declarations of a file and the classes they import.
+#[derive(Default)]
+struct KtWriter {
+    blocks: Vec<String>,
+    imports: BTreeSet<String>,
+}
+
+impl KtWriter {
+    fn import(&mut self, name: &str) {
+        self.imports.insert(name.to_string());
+    }
+}
+
+/// Kotlin files of `@Serializable` classes for kotlinx.serialization, like
+/// `models/User.kt` for `models/user.ts`.
+///
+/// The property names are camel case and `@SerialName` keeps the renamed
+/// fields. Enums with only unit variants are `enum class`es and the other
+/// tagged enums are `sealed class`es with generated serializers.
+#[derive(Debug, Clone, Default)]
+pub struct KotlinBackend {
+    // Only the names of the types and `integer_types` are used.
+    pub options: FormatOptions,
+    // The package of the root directory, like `com.example.models`.
+    pub package: String,
+}
+
+impl Backend for KotlinBackend {
+    // Generate a Kotlin file of `@Serializable` classes for every Typescript
+    // file, like `models/User.kt` in the package `{package}.models` for
+    // `models/user.ts`.
+    //
+    // APIs are not generated.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let options = &self.options;
+        let package = &self.package;
+        let names = TypeNames::new(&manager.descriptors, options);
+        manager
+            .declared_files()
+            .into_iter()
+            .map(|(file_name, idxs)| {
+                let (dir, class_file) = kotlin_file(file_name);
+                let current = package_name(package, &dir);
+                let own = idxs
+                    .iter()
+                    .map(|idx| {
+                        let (f, n) = manager.type_key(*idx);
+                        (f.to_string(), n.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                let imports = idxs
+                    .iter()
+                    .flat_map(|idx| manager.named_types(*idx))
+                    .filter(|(f, _)| f != file_name)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let (local, aliases) = names.local(file_name, &own, &imports);
+                let mut w = KtWriter::default();
+                imports
+                    .iter()
+                    .zip(aliases.iter())
+                    .for_each(|((f, _), (name, alias))| {
+                        let target = package_name(package, &kotlin_file(f).0);
+                        if target == current && alias.is_none() {
+                            return;
+                        }
+                        let import = match alias {
+                            Some(alias) => format!("{}.{} as {}", target, name, alias),
+                            None => format!("{}.{}", target, name),
+                        };
+                        w.import(import.trim_start_matches('.'));
+                    });
+                idxs.iter().for_each(|idx| {
+                    let generics = manager.generic_params(*idx);
+                    write_kotlin_type(manager, *idx, &generics, &local, options, &mut w);
+                });
+                let mut content = format!("{}\n", PREFIX);
+                if !current.is_empty() {
+                    content.push_str(&format!("package {}\n", current));
+                }
+                content.push('\n');
+                w.imports
+                    .iter()
+                    .for_each(|i| content.push_str(&format!("import {}\n", i)));
+                w.blocks.iter().for_each(|b| {
+                    content.push('\n');
+                    content.push_str(b);
+                });
+                (class_file, content)
+            })
+            .collect()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["kt"]
+    }
+}
+
+fn write_kotlin_type(
+    manager: &DescriptorManager,
+    idx: usize,
+    generics: &[String],
+    names: &LocalNames,
+    options: &FormatOptions,
+    w: &mut KtWriter,
+) {
+    let integers = &options.integer_types;
+    w.import("kotlinx.serialization.Serializable");
+    match manager.descriptors.get(idx).unwrap() {
+        Descriptor::Interface(d) => {
+            let name = &names[&(d.file_name.to_string(), d.ts_name.to_string())];
+            let generics = if generics.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", generics.join(", "))
+            };
+            let mut block = doc_comment(&d.comments, "");
+            block.push_str("@Serializable\n");
+            if d.fields.is_empty() {
+                block.push_str(&format!("class {}{}\n", name, generics));
+                w.blocks.push(block);
+                return;
+            }
+            block.push_str(&format!("data class {}{}(\n", name, generics));
+            d.fields.iter().for_each(|f| {
+                block.push_str(&doc_comment(&f.comments, "    "));
+                let property = to_camel(&f.ident);
+                if property != f.ident {
+                    w.import("kotlinx.serialization.SerialName");
+                    block.push_str(&format!("    @SerialName({})\n", quote(&f.ident)));
+                }
+                let ty = field_type(f, names, integers, w);
+                let default = if f.optional { " = null" } else { "" };
+                block.push_str(&format!(
+                    "    val {}: {}{},\n",
+                    identifier(&property),
+                    ty,
+                    default
+                ));
+            });
+            block.push_str(")\n");
+            w.blocks.push(block);
+        }
+        Descriptor::Enum(e) => {
+            let name = &names[&(e.file_name.to_string(), e.ts_name.to_string())];
+            if e.fields.iter().all(|f| f.ty.is_none()) {
+                write_enum_class(e, name, w);
+            } else {
+                write_sealed_class(e, name, names, integers, w);
+            }
+        }
+        _ => {}
+    }
+}
+
+// `models/user-info.ts` -> (`models`, `models/UserInfo.kt`)
+fn kotlin_file(file_name: &str) -> (String, String) {
+    let path = remove_ext(file_name).replace('-', "_");
+    match path.rsplit_once('/') {
+        Some((dir, stem)) => (dir.to_string(), format!("{}/{}.kt", dir, to_pascal(stem))),
+        None => (String::new(), format!("{}.kt", to_pascal(&path))),
+    }
+}
+
+// `com.example` and `models/auth` -> `com.example.models.auth`
+fn package_name(package: &str, dir: &str) -> String {
+    let dir = dir.replace('/', ".");
+    match (package.is_empty(), dir.is_empty()) {
+        (_, true) => package.to_string(),
+        (true, false) => dir,
+        (false, false) => format!("{}.{}", package, dir),
+    }
+}
+
+fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+    format!("\"{}\"", escaped)
+}
+
+fn doc_comment(comments: &[String], indent: &str) -> String {
+    match comments {
+        [] => String::new(),
+        [c] => format!("{}/** {} */\n", indent, c),
+        _ => {
+            let lines = comments
+                .iter()
+                .map(|c| format!("{} * {}\n", indent, c).replace(" * \n", " *\n"))
+                .collect::<String>();
+            format!("{}/**\n{}{} */\n", indent, lines, indent)
+        }
+    }
+}
+
+// Enums with only unit variants are serialized as their tag values.
+fn write_enum_class(e: &EnumDescriptor, name: &str, w: &mut KtWriter) {
+    w.import("kotlinx.serialization.SerialName");
+    let mut block = doc_comment(&e.comments, "");
+    block.push_str(&format!("@Serializable\nenum class {} {{\n", name));
+    e.fields.iter().for_each(|f| {
+        block.push_str(&doc_comment(&f.comments, "    "));
+        block.push_str(&format!("    @SerialName({})\n", quote(&f.tag_value)));
+        block.push_str(&format!("    {},\n", to_pascal(&f.ident)));
+    });
+    block.push_str("}\n");
+    w.blocks.push(block);
+}
+
+// Unit variants are the tag values and the others are objects like
+// `{ "type": "guest", "value": 1 }`, which kotlinx.serialization can not
+// describe by annotations, so a serializer is generated.
+fn write_sealed_class(
+    e: &EnumDescriptor,
+    name: &str,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut KtWriter,
+) {
+    [
+        "kotlinx.serialization.KSerializer",
+        "kotlinx.serialization.SerializationException",
+        "kotlinx.serialization.descriptors.SerialDescriptor",
+        "kotlinx.serialization.encoding.Decoder",
+        "kotlinx.serialization.encoding.Encoder",
+        "kotlinx.serialization.json.*",
+    ]
+    .iter()
+    .for_each(|i| w.import(i));
+    let serializer = format!("{}Serializer", name);
+    let mut block = doc_comment(&e.comments, "");
+    block.push_str(&format!(
+        "@Serializable(with = {}::class)\nsealed class {} {{\n",
+        serializer, name
+    ));
+    e.fields.iter().for_each(|f| {
+        block.push_str(&doc_comment(&f.comments, "    "));
+        let variant = to_pascal(&f.ident);
+        match &f.ty {
+            None => block.push_str(&format!("    object {} : {}()\n", variant, name)),
+            Some(ty) => block.push_str(&format!(
+                "    data class {}(val value: {}) : {}()\n",
+                variant,
+                type_name(ty, names, integers, w),
+                name
+            )),
+        }
+    });
+    block.push_str("}\n\n");
+
+    block.push_str(&format!(
+        "object {} : KSerializer<{}> {{\n",
+        serializer, name
+    ));
+    block.push_str(
+        "    override val descriptor: SerialDescriptor = JsonElement.serializer().descriptor\n\n",
+    );
+    block.push_str(&format!(
+        "    override fun serialize(encoder: Encoder, value: {}) {{\n",
+        name
+    ));
+    block.push_str("        val json = (encoder as JsonEncoder).json\n");
+    block.push_str("        val element = when (value) {\n");
+    e.fields.iter().for_each(|f| {
+        let variant = format!("{}.{}", name, to_pascal(&f.ident));
+        match &f.ty {
+            None => block.push_str(&format!(
+                "            is {} -> JsonPrimitive({})\n",
+                variant,
+                quote(&f.tag_value)
+            )),
+            Some(_) => {
+                block.push_str(&format!(
+                    "            is {} -> buildJsonObject {{\n",
+                    variant
+                ));
+                block.push_str(&format!(
+                    "                put({}, JsonPrimitive({}))\n",
+                    quote(&e.tag),
+                    quote(&f.tag_value)
+                ));
+                block.push_str(
+                    "                put(\"value\", json.encodeToJsonElement(value.value))\n",
+                );
+                block.push_str("            }\n");
+            }
+        }
+    });
+    block.push_str("        }\n");
+    block.push_str("        encoder.encodeJsonElement(element)\n");
+    block.push_str("    }\n\n");
+    block.push_str(&format!(
+        "    override fun deserialize(decoder: Decoder): {} {{\n",
+        name
+    ));
+    block.push_str("        val json = (decoder as JsonDecoder).json\n");
+    block.push_str("        val element = decoder.decodeJsonElement()\n");
+    block.push_str(&format!(
+        "        val tag = if (element is JsonPrimitive) element.content else element.jsonObject.getValue({}).jsonPrimitive.content\n",
+        quote(&e.tag)
+    ));
+    block.push_str("        return when (tag) {\n");
+    e.fields.iter().for_each(|f| {
+        let variant = format!("{}.{}", name, to_pascal(&f.ident));
+        match &f.ty {
+            None => block.push_str(&format!("            {} -> {}\n", quote(&f.tag_value), variant)),
+            Some(_) => block.push_str(&format!(
+                "            {} -> {}(json.decodeFromJsonElement(element.jsonObject.getValue(\"value\")))\n",
+                quote(&f.tag_value),
+                variant
+            )),
+        }
+    });
+    block.push_str(&format!(
+        "            else -> throw SerializationException(\"unknown {}: $tag\")\n",
+        name
+    ));
+    block.push_str("        }\n");
+    block.push_str("    }\n");
+    block.push_str("}\n");
+    w.blocks.push(block);
+}
+
+// Optional fields may be omitted, like `age?: number` in Typescript.
+fn field_type(
+    f: &FieldDescriptor,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut KtWriter,
+) -> String {
+    match &f.ty {
+        Some(TypeExpr::Option(t)) if f.optional => {
+            format!("{}?", type_name(t, names, integers, w))
+        }
+        Some(ty) => type_name(ty, names, integers, w),
+        None => json_element(w),
+    }
+}
+
+fn json_element(w: &mut KtWriter) -> String {
+    w.import("kotlinx.serialization.json.JsonElement");
+    String::from("JsonElement")
+}
+
+fn type_name(
+    ty: &TypeExpr,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut KtWriter,
+) -> String {
+    match ty {
+        TypeExpr::Primitive(tag) => {
+            if integers.get(*tag).is_some_and(|t| t == "string") {
+                return String::from("String");
+            }
+            let name = match *tag {
+                "string" => "String",
+                "bool" => "Boolean",
+                "f32" => "Float",
+                "f64" => "Double",
+                "u8" => "UByte",
+                "u16" => "UShort",
+                "u32" => "UInt",
+                "u64" | "usize" => "ULong",
+                "i8" => "Byte",
+                "i16" => "Short",
+                "i32" => "Int",
+                _ => "Long",
+            };
+            name.to_string()
+        }
+        TypeExpr::Param(name) => name.to_string(),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name).to_string();
+            if args.is_empty() {
+                return name;
+            }
+            let args = args
+                .iter()
+                .map(|a| type_name(a, names, integers, w))
+                .collect::<Vec<_>>();
+            format!("{}<{}>", name, args.join(", "))
+        }
+        TypeExpr::Array(t) => format!("List<{}>", type_name(t, names, integers, w)),
+        TypeExpr::Option(t) => format!("{}?", type_name(t, names, integers, w)),
+        TypeExpr::Map(k, v) => {
+            let k = type_name(k, names, integers, w);
+            let v = type_name(v, names, integers, w);
+            format!("Map<{}, {}>", k, v)
+        }
+        // Tuples are JSON arrays of mixed types, and the others are not
+        // known by gents.
+        TypeExpr::Result(..) | TypeExpr::Tuple(_) | TypeExpr::Raw(_) => json_element(w),
+    }
+}
@@ -54,13 +54,55 @@
 //! ## Step4: Run the unit test
 //!
 
+mod backend;
+#[cfg(feature = "build")]
+pub mod build;
+mod client;
+mod config;
 mod descriptor;
+mod dispatch;
 mod file_generator;
+mod guards;
+mod json_schema;
+mod kotlin;
+mod naming;
+mod openapi;
+mod python;
+mod registry;
+mod swift;
+mod tauri;
 mod ts_formatter;
+mod typescript;
 mod utils;
+mod wasm;
+mod zod;
 
+pub use backend::Backend;
+pub use config::{
+    Casing, Config, FormatConfig, GroupConfig, IndentConfig, IndentName, IntegerType,
+};
 pub use descriptor::*;
+pub use dispatch::*;
 pub use file_generator::*;
+pub use json_schema::JsonSchemaBackend;
+pub use kotlin::KotlinBackend;
+pub use naming::{FileNaming, NameCollision};
+pub use openapi::OpenApiBackend;
+pub use python::PythonBackend;
+pub use registry::_Exported;
+pub use swift::SwiftBackend;
+pub use ts_formatter::{FormatOptions, ImportExtension, Indent, LineEnding, QuoteStyle};
+pub use typescript::TypescriptBackend;
+pub use wasm::WasmBindgenBackend;
 
+#[doc(hidden)]
+pub use inventory;
 pub use serde;
+pub use serde_json;
+#[cfg(feature = "wasm")]
+pub use serde_wasm_bindgen;
 pub use serde_with;
+#[cfg(feature = "wasm")]
+pub use wasm_bindgen;
+#[cfg(feature = "wasm")]
+pub use wasm_bindgen_futures;
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::descriptor::Descriptor;
+use crate::ts_formatter::FormatOptions;
+use crate::utils::{remove_ext, to_snake_case};
+
+/// How to deal with the types sharing the same name in different files, or
+/// named after the builtin types of Typescript like `Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameCollision {
+    // Generate the code as it is.
+    Ignore,
+    // Panic with the conflicts in a file when generating it, or in all the
+    // files if they are generated into one namespace.
+    Error,
+    // Import the conflicting types with aliases, like
+    // `import { Error as DbError } from './db'`, or rename them if they are
+    // generated into one namespace.
+    Alias,
+}
+
+/// Where to place the types without `#[ts(file_name = "...")]`, according
+/// to the Rust modules declaring them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileNaming {
+    // One file per module: `my_crate::models::user::User` -> `models/user.ts`.
+    // Types in the crate root are placed in the files named after themselves.
+    #[default]
+    Module,
+    // One file per type: `my_crate::models::user::UserProfile` -> `models/user/user_profile.ts`.
+    Type,
+}
+
+impl FileNaming {
+    /// Get the file name from `module_path!()` and the name of the type.
+    pub fn file_name(&self, module_path: &str, ts_name: &str) -> String {
+        // Skip the crate name.
+        let dir = module_path.split("::").skip(1).collect::<Vec<_>>();
+        let type_file = to_snake_case(ts_name);
+        match (self, dir.is_empty()) {
+            (_, true) => format!("{}.ts", type_file),
+            (FileNaming::Module, false) => format!("{}.ts", dir.join("/")),
+            (FileNaming::Type, false) => format!("{}/{}.ts", dir.join("/"), type_file),
+        }
+    }
+}
+
+// The builtin names of Typescript which will be shadowed by the imports.
+const RESERVED_NAMES: &[&str] = &[
+    "Array",
+    "ArrayBuffer",
+    "BigInt",
+    "Boolean",
+    "Date",
+    "Error",
+    "Function",
+    "JSON",
+    "Map",
+    "Math",
+    "Number",
+    "Object",
+    "Omit",
+    "Partial",
+    "Pick",
+    "Promise",
+    "Readonly",
+    "ReadonlyArray",
+    "ReadonlyMap",
+    "ReadonlySet",
+    "Record",
+    "RegExp",
+    "Required",
+    "Set",
+    "String",
+    "Symbol",
+    "Uint8Array",
+];
+
+// Identify a type by the file declaring it and its original ts name.
+pub(crate) type TypeKey = (String, String);
+
+// The names of the types used in a file.
+pub(crate) type LocalNames = HashMap<TypeKey, String>;
+
+pub(crate) struct TypeNames {
+    names: LocalNames,
+    // The names in one namespace, in which the conflicting types are renamed
+    // after their files if aliases are enabled.
+    global: LocalNames,
+    collision: NameCollision,
+}
+
+impl TypeNames {
+    pub fn new(descriptors: &[Descriptor], options: &FormatOptions) -> Self {
+        let mut names = LocalNames::new();
+        descriptors.iter().for_each(|d| {
+            let (file_name, ts_name) = match d {
+                Descriptor::Interface(d) => (&d.file_name, &d.ts_name),
+                Descriptor::Enum(e) => (&e.file_name, &e.ts_name),
+                _ => return,
+            };
+            let name = format!("{}{}{}", options.type_prefix, ts_name, options.type_suffix);
+            names.insert((file_name.to_string(), ts_name.to_string()), name);
+        });
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        names
+            .values()
+            .for_each(|n| *counts.entry(n).or_default() += 1);
+        let global = names
+            .iter()
+            .map(|(k, name)| {
+                let conflicted =
+                    counts[name.as_str()] > 1 || RESERVED_NAMES.contains(&name.as_str());
+                if options.name_collision == NameCollision::Alias && conflicted {
+                    (k.clone(), alias_name(&k.0, name))
+                } else {
+                    (k.clone(), name.clone())
+                }
+            })
+            .collect();
+        TypeNames {
+            names,
+            global,
+            collision: options.name_collision,
+        }
+    }
+
+    // The names for the outputs sharing one namespace, like a bundle, in which
+    // aliases are not available.
+    pub fn global(&self) -> &LocalNames {
+        if self.collision == NameCollision::Error {
+            let conflicts = self.conflicts();
+            if !conflicts.is_empty() {
+                panic!("type name conflicts: {}", conflicts.join("; "));
+            }
+        }
+        &self.global
+    }
+
+    // The names of the files which refer to each other by paths, like the
+    // JSON schemas, so they never conflict.
+    pub fn all(&self) -> &LocalNames {
+        &self.names
+    }
+
+    pub fn get(&self, key: &TypeKey) -> &str {
+        &self.names[key]
+    }
+
+    /// Get the names used in a file which declares `own` and imports `imports`,
+    /// with the aliases of the imports if any.
+    pub fn local(
+        &self,
+        current: &str,
+        own: &[TypeKey],
+        imports: &[TypeKey],
+    ) -> (LocalNames, Vec<(String, Option<String>)>) {
+        let keys = own.iter().chain(imports.iter()).collect::<HashSet<_>>();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        keys.into_iter().for_each(|k| {
+            *counts.entry(self.get(k)).or_default() += 1;
+        });
+        if self.collision == NameCollision::Error {
+            let conflicts = self.local_conflicts(own, imports, &counts);
+            if !conflicts.is_empty() {
+                panic!(
+                    "type name conflicts in {}: {}",
+                    current,
+                    conflicts.join("; ")
+                );
+            }
+        }
+        let mut local = self.names.clone();
+        let aliases = imports
+            .iter()
+            .map(|k| {
+                let name = self.get(k).to_string();
+                let conflicted =
+                    counts[name.as_str()] > 1 || RESERVED_NAMES.contains(&name.as_str());
+                if self.collision != NameCollision::Alias || !conflicted {
+                    return (name, None);
+                }
+                let alias = alias_name(&k.0, &name);
+                local.insert(k.clone(), alias.clone());
+                (name, Some(alias))
+            })
+            .collect();
+        (local, aliases)
+    }
+
+    // The duplicated names in the file, and the declarations or the imports
+    // shadowing the builtin types which may be used in the file, like
+    // `throw new Error(...)` in the builders.
+    fn local_conflicts(
+        &self,
+        own: &[TypeKey],
+        imports: &[TypeKey],
+        counts: &HashMap<&str, usize>,
+    ) -> Vec<String> {
+        let mut files: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        own.iter().chain(imports.iter()).for_each(|k| {
+            let name = self.get(k);
+            if counts[name] > 1 {
+                files.entry(name).or_default().insert(&k.0);
+            }
+        });
+        let mut result = files
+            .into_iter()
+            .map(|(name, files)| {
+                let files = files.into_iter().collect::<Vec<_>>();
+                format!("`{}` is declared in {}", name, files.join(", "))
+            })
+            .collect::<Vec<_>>();
+        own.iter()
+            .chain(imports.iter())
+            .map(|k| self.get(k))
+            .filter(|name| RESERVED_NAMES.contains(name))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .for_each(|name| result.push(format!("`{}` is a builtin type of Typescript", name)));
+        result.sort();
+        result
+    }
+
+    fn conflicts(&self) -> Vec<String> {
+        let mut files: HashMap<&str, Vec<&str>> = HashMap::new();
+        self.names.iter().for_each(|((file_name, _), name)| {
+            files.entry(name).or_default().push(file_name);
+        });
+        let mut result = files
+            .into_iter()
+            .flat_map(|(name, mut files)| {
+                files.sort();
+                let mut conflicts = vec![];
+                if files.len() > 1 {
+                    conflicts.push(format!("`{}` is declared in {}", name, files.join(", ")));
+                }
+                if RESERVED_NAMES.contains(&name) {
+                    conflicts.push(format!("`{}` is a builtin type of Typescript", name));
+                }
+                conflicts
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+}
+
+// `db.ts` and `Error` -> `DbError`
+// `api/error.ts` and `Error` -> `ApiError`
+fn alias_name(file_name: &str, name: &str) -> String {
+    let path = remove_ext(file_name);
+    let mut parts = path.split('/').map(to_pascal).collect::<Vec<_>>();
+    if parts.len() > 1 && parts.last().is_some_and(|p| p == name) {
+        parts.pop();
+    }
+    format!("{}{}", parts.join(""), name)
+}
+
+// `db_models` -> `DbModels`
+pub(crate) fn to_pascal(s: &str) -> String {
+    s.split(['_', '-', '.'])
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// `user_name` -> `userName`
+pub(crate) fn to_camel(s: &str) -> String {
+    let pascal = to_pascal(s);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,215 @@
+use serde_json::{json, Map, Value};
+
+use crate::backend::Backend;
+use crate::descriptor::{ApiDescriptor, DescriptorManager, MethodDescriptor, TypeExpr};
+use crate::json_schema::{descriptor_schema, schema_type, to_json, type_schema, Refs};
+use crate::naming::{to_camel, to_pascal, LocalNames, TypeNames};
+use crate::ts_formatter::FormatOptions;
+
+const VERSION: &str = "3.1.0";
+
+// The methods whose params are passed in the query string.
+const QUERY_METHODS: &[&str] = &["GET", "DELETE", "HEAD", "OPTIONS"];
+
+/// An OpenAPI 3.1 document of the types and the APIs, `openapi.json`.
+///
+/// See `#[ts(http = "...")]` of `#[ts_interface]` for how the methods are
+/// mapped to the operations.
+#[derive(Debug, Clone)]
+pub struct OpenApiBackend {
+    // Only the names of the types are used.
+    pub options: FormatOptions,
+    // `info.title` and `info.version` of the document.
+    pub title: String,
+    pub version: String,
+}
+
+impl Default for OpenApiBackend {
+    fn default() -> Self {
+        OpenApiBackend {
+            options: FormatOptions::default(),
+            title: String::from("API"),
+            version: String::from("0.1.0"),
+        }
+    }
+}
+
+impl Backend for OpenApiBackend {
+    // Generate an OpenAPI 3.1 document, with all the types under
+    // `components/schemas` and an operation for every API method.
+    //
+    // Methods without `#[ts(http = "...")]` are `POST /{api}/{method}`.
+    // Params named by the path templates are path parameters. The others are
+    // query parameters for methods like `GET` and `DELETE`, or the JSON body,
+    // which is an object of the params if there are more than one.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let options = &self.options;
+        let names = TypeNames::new(&manager.descriptors, options);
+        let schemas = manager
+            .descriptors
+            .iter()
+            .filter_map(|d| {
+                let (file_name, ts_name) = schema_type(d)?;
+                let name = &names.global()[&(file_name.to_string(), ts_name.to_string())];
+                let schema = descriptor_schema(d, names.global(), &Refs::Components);
+                Some((name.to_string(), schema))
+            })
+            .collect::<Map<_, _>>();
+        let mut paths = Map::new();
+        manager.apis().iter().for_each(|api| {
+            api.methods.iter().for_each(|m| {
+                let (method, path) = route(api, m);
+                let item = paths
+                    .entry(path.to_string())
+                    .or_insert_with(|| json!({}))
+                    .as_object_mut()
+                    .unwrap();
+                let key = method.to_lowercase();
+                if item.contains_key(&key) {
+                    panic!("duplicated operation: {} {}", method, path);
+                }
+                item.insert(key, operation(api, m, &method, &path, names.global()));
+            });
+        });
+        let content = to_json(json!({
+            "openapi": VERSION,
+            "info": { "title": self.title, "version": self.version },
+            "paths": paths,
+            "components": { "schemas": schemas },
+        }));
+        vec![(String::from("openapi.json"), content)]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+// Where a param of a method is sent in the REST request.
+pub(crate) enum ParamIn {
+    // The name in the path template, like `user_id` in `/users/{user_id}`.
+    Path(String),
+    Query,
+    Body,
+}
+
+// `POST /users` of `#[ts(http = "POST /users")]`, or `POST /UserApi/getUser`.
+pub(crate) fn route(api: &ApiDescriptor, m: &MethodDescriptor) -> (String, String) {
+    match m.http.as_deref().and_then(|h| h.split_once(' ')) {
+        Some((method, path)) => (method.to_string(), path.to_string()),
+        None => (String::from("POST"), format!("/{}/{}", api.name, m.name)),
+    }
+}
+
+// `/users/{user_id}` -> `["user_id"]`
+fn path_params(path: &str) -> Vec<&str> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}').map(|(p, _)| p))
+        .collect()
+}
+
+// The params are camel case and the path templates may be snake case.
+pub(crate) fn param_locations(m: &MethodDescriptor, method: &str, path: &str) -> Vec<ParamIn> {
+    let templates = path_params(path);
+    m.params
+        .iter()
+        .map(
+            |(name, _)| match templates.iter().find(|t| to_camel(t) == *name) {
+                Some(t) => ParamIn::Path(t.to_string()),
+                None if QUERY_METHODS.contains(&method) => ParamIn::Query,
+                None => ParamIn::Body,
+            },
+        )
+        .collect()
+}
+
+fn operation(
+    api: &ApiDescriptor,
+    m: &MethodDescriptor,
+    method: &str,
+    path: &str,
+    names: &LocalNames,
+) -> Value {
+    let mut op = Map::new();
+    op.insert(
+        String::from("operationId"),
+        json!(format!("{}{}", to_camel(&api.name), to_pascal(&m.name))),
+    );
+    op.insert(String::from("tags"), json!([api.name]));
+    if !m.comment.is_empty() {
+        op.insert(String::from("description"), json!(m.comment.join("\n")));
+    }
+
+    let mut parameters = vec![];
+    let mut body = vec![];
+    param_locations(m, method, path)
+        .into_iter()
+        .zip(m.params.iter().zip(m.param_types.iter()))
+        .for_each(|(location, ((name, _), ty))| {
+            let (location, name) = match location {
+                ParamIn::Path(t) => ("path", t),
+                ParamIn::Query => ("query", name.to_string()),
+                ParamIn::Body => {
+                    body.push((name, ty));
+                    return;
+                }
+            };
+            let (ty, required) = match ty {
+                TypeExpr::Option(t) => (t.as_ref(), location == "path"),
+                _ => (ty, true),
+            };
+            parameters.push(json!({
+                "name": name,
+                "in": location,
+                "required": required,
+                "schema": type_schema(ty, names, &Refs::Components),
+            }));
+        });
+    if !parameters.is_empty() {
+        op.insert(String::from("parameters"), json!(parameters));
+    }
+    let body_schema = match body.as_slice() {
+        [] => None,
+        [(_, ty)] => Some(type_schema(ty, names, &Refs::Components)),
+        _ => {
+            let properties = body
+                .iter()
+                .map(|(n, t)| (n.to_string(), type_schema(t, names, &Refs::Components)))
+                .collect::<Map<_, _>>();
+            let required = body
+                .iter()
+                .filter(|(_, t)| !matches!(t, TypeExpr::Option(_)))
+                .map(|(n, _)| json!(n))
+                .collect::<Vec<_>>();
+            Some(json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }))
+        }
+    };
+    if let Some(schema) = body_schema {
+        op.insert(
+            String::from("requestBody"),
+            json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema } },
+            }),
+        );
+    }
+
+    let responses = match &m.return_type_expr {
+        Some(ty) => json!({
+            "200": {
+                "description": "OK",
+                "content": {
+                    "application/json": { "schema": type_schema(ty, names, &Refs::Components) },
+                },
+            },
+        }),
+        None => json!({ "204": { "description": "No Content" } }),
+    };
+    op.insert(String::from("responses"), responses);
+    Value::Object(op)
+}
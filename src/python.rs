@@ -0,0 +1,415 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::Backend;
+use crate::descriptor::{Descriptor, DescriptorManager, FieldDescriptor, TypeExpr};
+use crate::naming::{to_pascal, LocalNames, TypeKey, TypeNames};
+use crate::ts_formatter::FormatOptions;
+use crate::utils::remove_ext;
+
+const HEADER: &str = "# DO NOT EDIT. CODE GENERATED BY gents.";
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+// The declarations of a file and the names they use from `typing`.
+#[derive(Default)]
+struct PyWriter {
+    blocks: Vec<String>,
+    typing: BTreeSet<&'static str>,
+}
+
+// A key of a `TypedDict`.
+struct PyField {
+    name: String,
+    ty: String,
+    comments: Vec<String>,
+}
+
+/// Python modules of `TypedDict`s mirroring the Typescript files, like
+/// `models/user.py` for `models/user.ts`.
+///
+/// `Option` fields are `NotRequired` and tagged enums are unions of the
+/// `Literal` tag values and the `TypedDict`s of the variants. `NotRequired`
+/// needs Python 3.11 or later.
+#[derive(Debug, Clone, Default)]
+pub struct PythonBackend {
+    // Only the names of the types and `integer_types` are used.
+    pub options: FormatOptions,
+}
+
+impl Backend for PythonBackend {
+    // Generate a Python module of `TypedDict`s for every Typescript file,
+    // like `models/user.py` for `models/user.ts`. The modules import each
+    // other relatively, and every directory gets an `__init__.py`.
+    //
+    // APIs are not generated.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let options = &self.options;
+        let names = TypeNames::new(&manager.descriptors, options);
+        let mut packages = BTreeSet::new();
+        packages.insert(String::new());
+        let mut result = manager
+            .declared_files()
+            .into_iter()
+            .map(|(file_name, idxs)| {
+                let module = module_path(file_name);
+                let mut parent = module.as_str();
+                while let Some((dir, _)) = parent.rsplit_once('/') {
+                    packages.insert(dir.to_string());
+                    parent = dir;
+                }
+                let own = idxs
+                    .iter()
+                    .map(|idx| {
+                        let (f, n) = manager.type_key(*idx);
+                        (f.to_string(), n.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                let imports = idxs
+                    .iter()
+                    .flat_map(|idx| manager.named_types(*idx))
+                    .filter(|(f, _)| f != file_name)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let (local, aliases) = names.local(file_name, &own, &imports);
+                let mut w = PyWriter::default();
+                let mut params = BTreeSet::new();
+                idxs.iter().for_each(|idx| {
+                    let generics = manager.generic_params(*idx);
+                    params.extend(generics.iter().cloned());
+                    write_python_type(manager, *idx, &generics, &local, options, &mut w);
+                });
+                let content = python_file(&module, &imports, &aliases, &params, w);
+                (format!("{}.py", module), content)
+            })
+            .collect::<Vec<_>>();
+        packages.into_iter().for_each(|p| {
+            let file_name = if p.is_empty() {
+                String::from("__init__.py")
+            } else {
+                format!("{}/__init__.py", p)
+            };
+            result.push((file_name, format!("{}\n", HEADER)));
+        });
+        result
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["py"]
+    }
+}
+
+fn write_python_type(
+    manager: &DescriptorManager,
+    idx: usize,
+    generics: &[String],
+    names: &LocalNames,
+    options: &FormatOptions,
+    w: &mut PyWriter,
+) {
+    let integers = &options.integer_types;
+    match manager.descriptors.get(idx).unwrap() {
+        Descriptor::Interface(d) => {
+            let name = &names[&(d.file_name.to_string(), d.ts_name.to_string())];
+            let fields = d
+                .fields
+                .iter()
+                .map(|f| PyField {
+                    name: f.ident.to_string(),
+                    ty: field_type(f, names, integers, w),
+                    comments: f.comments.clone(),
+                })
+                .collect::<Vec<_>>();
+            write_typed_dict(name, generics, &d.comments, fields, w);
+        }
+        Descriptor::Enum(e) => {
+            // Unit variants are the literals of their tag values and the
+            // others are `TypedDict`s like `{ "type": "guest", "value": 1 }`.
+            let name = &names[&(e.file_name.to_string(), e.ts_name.to_string())];
+            let params = if generics.is_empty() {
+                String::new()
+            } else {
+                format!("[{}]", generics.join(", "))
+            };
+            let mut literals = vec![];
+            let mut variants = vec![];
+            e.fields.iter().for_each(|f| match &f.ty {
+                None => literals.push(quote(&f.tag_value)),
+                Some(ty) => {
+                    w.typing.insert("Literal");
+                    let variant = format!("{}{}", name, to_pascal(&f.ident));
+                    let fields = vec![
+                        PyField {
+                            name: e.tag.to_string(),
+                            ty: format!("Literal[{}]", quote(&f.tag_value)),
+                            comments: vec![],
+                        },
+                        PyField {
+                            name: String::from("value"),
+                            ty: type_name(ty, names, integers, w),
+                            comments: vec![],
+                        },
+                    ];
+                    write_typed_dict(&variant, generics, &f.comments, fields, w);
+                    variants.push(format!("{}{}", variant, params));
+                }
+            });
+            if !literals.is_empty() {
+                w.typing.insert("Literal");
+                variants.insert(0, format!("Literal[{}]", literals.join(", ")));
+            }
+            let value = match variants.len() {
+                0 => {
+                    w.typing.insert("Never");
+                    String::from("Never")
+                }
+                1 => variants.remove(0),
+                _ => {
+                    w.typing.insert("Union");
+                    format!("Union[{}]", variants.join(", "))
+                }
+            };
+            let comments = e
+                .comments
+                .iter()
+                .map(|c| format!("# {}\n", c).replace("# \n", "#\n"))
+                .collect::<String>();
+            w.blocks.push(format!("{}{} = {}\n", comments, name, value));
+        }
+        _ => {}
+    }
+}
+
+// `models/user-info.ts` -> `models/user_info`
+fn module_path(file_name: &str) -> String {
+    remove_ext(file_name).replace('-', "_")
+}
+
+// The module of `to` relative to the package of `from`.
+// `models/user` and `models/page` -> `.page`
+// `models/user` and `role` -> `..role`
+fn relative_module(from: &str, to: &str) -> String {
+    let from_dir = from.split('/').collect::<Vec<_>>();
+    let from_dir = &from_dir[..from_dir.len() - 1];
+    let to = to.split('/').collect::<Vec<_>>();
+    let common = from_dir
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let ups = from_dir.len() - common;
+    format!("{}{}", ".".repeat(ups + 1), to[common..].join("."))
+}
+
+fn python_file(
+    module: &str,
+    imports: &[TypeKey],
+    aliases: &[(String, Option<String>)],
+    params: &BTreeSet<String>,
+    mut w: PyWriter,
+) -> String {
+    if !params.is_empty() {
+        w.typing.insert("TypeVar");
+    }
+    let mut result = format!("{}\nfrom __future__ import annotations\n\n", HEADER);
+    let typing = w.typing.iter().copied().collect::<Vec<_>>();
+    result.push_str(&format!("from typing import {}\n", typing.join(", ")));
+    // module -> imported names
+    let mut modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    imports
+        .iter()
+        .zip(aliases.iter())
+        .for_each(|((f, _), (name, alias))| {
+            let name = match alias {
+                Some(alias) => format!("{} as {}", name, alias),
+                None => name.to_string(),
+            };
+            modules
+                .entry(relative_module(module, &module_path(f)))
+                .or_default()
+                .push(name);
+        });
+    if !modules.is_empty() {
+        result.push('\n');
+    }
+    modules.into_iter().for_each(|(m, names)| {
+        result.push_str(&format!("from {} import {}\n", m, names.join(", ")));
+    });
+    if !params.is_empty() {
+        result.push('\n');
+    }
+    params.iter().for_each(|p| {
+        result.push_str(&format!("{} = TypeVar({})\n", p, quote(p)));
+    });
+    w.blocks.iter().for_each(|b| {
+        result.push_str("\n\n");
+        result.push_str(b);
+    });
+    result
+}
+
+// Keys which are not identifiers need the functional syntax, in which the
+// comments are dropped.
+fn write_typed_dict(
+    name: &str,
+    generics: &[String],
+    comments: &[String],
+    fields: Vec<PyField>,
+    w: &mut PyWriter,
+) {
+    w.typing.insert("TypedDict");
+    if !fields.iter().all(|f| identifier(&f.name)) {
+        let items = fields
+            .iter()
+            .map(|f| format!("{}: {}", quote(&f.name), f.ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+        w.blocks.push(format!(
+            "{} = TypedDict({}, {{{}}})\n",
+            name,
+            quote(name),
+            items
+        ));
+        return;
+    }
+    let bases = if generics.is_empty() {
+        String::from("TypedDict")
+    } else {
+        w.typing.insert("Generic");
+        format!("TypedDict, Generic[{}]", generics.join(", "))
+    };
+    let mut block = format!("class {}({}):\n", name, bases);
+    match comments {
+        [] => {}
+        [c] => block.push_str(&format!("    \"\"\"{}\"\"\"\n", c)),
+        _ => {
+            block.push_str("    \"\"\"");
+            comments.iter().enumerate().for_each(|(i, c)| {
+                let indent = if i == 0 || c.is_empty() { "" } else { "    " };
+                block.push_str(&format!("{}{}\n", indent, c));
+            });
+            block.push_str("    \"\"\"\n");
+        }
+    }
+    if !comments.is_empty() && !fields.is_empty() {
+        block.push('\n');
+    }
+    fields.iter().for_each(|f| {
+        f.comments.iter().for_each(|c| {
+            block.push_str(&format!("    # {}\n", c).replace("# \n", "#\n"));
+        });
+        block.push_str(&format!("    {}: {}\n", f.name, f.ty));
+    });
+    if comments.is_empty() && fields.is_empty() {
+        block.push_str("    pass\n");
+    }
+    w.blocks.push(block);
+}
+
+fn identifier(name: &str) -> bool {
+    let valid = name
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    valid && !name.is_empty() && !KEYWORDS.contains(&name)
+}
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+// Optional fields may be omitted, like `age?: number` in Typescript.
+fn field_type(
+    f: &FieldDescriptor,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut PyWriter,
+) -> String {
+    match &f.ty {
+        Some(TypeExpr::Option(t)) if f.optional => {
+            w.typing.insert("NotRequired");
+            format!("NotRequired[{}]", type_name(t, names, integers, w))
+        }
+        Some(ty) => type_name(ty, names, integers, w),
+        None => {
+            w.typing.insert("Any");
+            String::from("Any")
+        }
+    }
+}
+
+fn type_name(
+    ty: &TypeExpr,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut PyWriter,
+) -> String {
+    match ty {
+        TypeExpr::Primitive(tag) => match *tag {
+            "string" => String::from("str"),
+            "bool" => String::from("bool"),
+            "f32" | "f64" => String::from("float"),
+            // Integers may be written as strings, like `u64 = "string"`.
+            _ => match integers.get(*tag).map(|s| s.as_str()) {
+                Some("string") => String::from("str"),
+                _ => String::from("int"),
+            },
+        },
+        TypeExpr::Param(name) => name.to_string(),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name).to_string();
+            if args.is_empty() {
+                return name;
+            }
+            let args = args
+                .iter()
+                .map(|a| type_name(a, names, integers, w))
+                .collect::<Vec<_>>();
+            format!("{}[{}]", name, args.join(", "))
+        }
+        TypeExpr::Array(t) => {
+            if let TypeExpr::Primitive("u8") = t.as_ref() {
+                return String::from("bytes");
+            }
+            format!("list[{}]", type_name(t, names, integers, w))
+        }
+        TypeExpr::Option(t) => {
+            w.typing.insert("Optional");
+            format!("Optional[{}]", type_name(t, names, integers, w))
+        }
+        TypeExpr::Result(t, e) => {
+            w.typing.insert("Union");
+            let t = type_name(t, names, integers, w);
+            let e = type_name(e, names, integers, w);
+            format!("Union[{}, {}]", t, e)
+        }
+        TypeExpr::Tuple(items) if items.is_empty() => String::from("tuple[()]"),
+        TypeExpr::Tuple(items) => {
+            let items = items
+                .iter()
+                .map(|i| type_name(i, names, integers, w))
+                .collect::<Vec<_>>();
+            format!("tuple[{}]", items.join(", "))
+        }
+        TypeExpr::Map(k, v) => {
+            let k = type_name(k, names, integers, w);
+            let v = type_name(v, names, integers, w);
+            format!("dict[{}, {}]", k, v)
+        }
+        // Not known by gents.
+        TypeExpr::Raw(_) => {
+            w.typing.insert("Any");
+            String::from("Any")
+        }
+    }
+}
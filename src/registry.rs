@@ -0,0 +1,31 @@
+use crate::descriptor::DescriptorManager;
+
+/// A type or an API marked with `#[ts(export)]` or `#[ts_interface(export)]`.
+/// This should not be used directly - it's collected at link time and used by
+/// `FileGroup::add_all` and `FileGroup::add_by_tag`.
+#[doc(hidden)]
+pub struct _Exported {
+    pub name: &'static str,
+    pub module_path: &'static str,
+    pub tags: &'static [&'static str],
+    pub register: fn(&mut DescriptorManager),
+}
+
+inventory::collect!(_Exported);
+
+// All the exported items in the binary, sorted so that the output does not
+// depend on the link order.
+pub(crate) fn exported_items() -> Vec<&'static _Exported> {
+    let mut items = inventory::iter::<_Exported>.into_iter().collect::<Vec<_>>();
+    items.sort_by_key(|e| (e.module_path, e.name));
+    items
+}
+
+// `my_crate::models` matches `my_crate::models` and `my_crate::models::user`,
+// but not `my_crate::models_v2`.
+pub(crate) fn in_module(module_path: &str, prefix: &str) -> bool {
+    match module_path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
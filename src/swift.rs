@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+
+use crate::backend::Backend;
+use crate::descriptor::{Descriptor, DescriptorManager, EnumDescriptor, FieldDescriptor, TypeExpr};
+use crate::file_generator::PREFIX;
+use crate::naming::{to_camel, to_pascal, LocalNames, TypeNames};
+use crate::ts_formatter::FormatOptions;
+use crate::utils::remove_ext;
+
+const KEYWORDS: &[&str] = &[
+    "Any",
+    "Self",
+    "as",
+    "associatedtype",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "deinit",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "fileprivate",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "open",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "rethrows",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "subscript",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typealias",
+    "var",
+    "where",
+    "while",
+];
+
+const JSON_VALUE_FILE: &str = "JSONValue.swift";
+
+// Any JSON value, for the tuples and the types not known by gents.
+const JSON_VALUE: &str = r#"public enum JSONValue: Codable, Equatable {
+    case null
+    case bool(Bool)
+    case number(Double)
+    case string(String)
+    case array([JSONValue])
+    case object([String: JSONValue])
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .null: try container.encodeNil()
+        case .bool(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .string(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        }
+    }
+}
+"#;
+
+// The declarations of a file.
+#[derive(Default)]
+struct SwiftWriter {
+    blocks: Vec<String>,
+    // Whether `JSONValue` is used.
+    json_value: bool,
+}
+
+/// Swift files of `Codable` types, like `models/User.swift` for
+/// `models/user.ts`.
+///
+/// The property names are camel case and `CodingKeys` keeps the renamed
+/// fields. Enums with only unit variants are `String` enums and the other
+/// tagged enums have associated values.
+#[derive(Debug, Clone, Default)]
+pub struct SwiftBackend {
+    // Only the names of the types and `integer_types` are used.
+    pub options: FormatOptions,
+}
+
+impl Backend for SwiftBackend {
+    // Generate a Swift file of `Codable` types for every Typescript file,
+    // like `models/User.swift` for `models/user.ts`. All the files belong to
+    // one module, so there are no imports between them.
+    //
+    // APIs are not generated.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let options = &self.options;
+        let names = TypeNames::new(&manager.descriptors, options);
+        let mut json_value = false;
+        let mut result = manager
+            .declared_files()
+            .into_iter()
+            .map(|(file_name, idxs)| {
+                let mut w = SwiftWriter::default();
+                idxs.iter().for_each(|idx| {
+                    let generics = manager.generic_params(*idx);
+                    write_swift_type(manager, *idx, &generics, names.global(), options, &mut w);
+                });
+                json_value |= w.json_value;
+                let mut content = format!("{}\nimport Foundation\n", PREFIX);
+                w.blocks.iter().for_each(|b| {
+                    content.push('\n');
+                    content.push_str(b);
+                });
+                (swift_file(file_name), content)
+            })
+            .collect::<Vec<_>>();
+        if json_value {
+            let content = format!("{}\nimport Foundation\n\n{}", PREFIX, JSON_VALUE);
+            result.push((String::from(JSON_VALUE_FILE), content));
+        }
+        result
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["swift"]
+    }
+}
+
+fn write_swift_type(
+    manager: &DescriptorManager,
+    idx: usize,
+    generics: &[String],
+    names: &LocalNames,
+    options: &FormatOptions,
+    w: &mut SwiftWriter,
+) {
+    let integers = &options.integer_types;
+    match manager.descriptors.get(idx).unwrap() {
+        Descriptor::Interface(d) => {
+            let name = &names[&(d.file_name.to_string(), d.ts_name.to_string())];
+            let generics = if generics.is_empty() {
+                String::new()
+            } else {
+                let params = generics
+                    .iter()
+                    .map(|g| format!("{}: Codable", g))
+                    .collect::<Vec<_>>();
+                format!("<{}>", params.join(", "))
+            };
+            let mut block = doc_comment(&d.comments, "");
+            if d.fields.is_empty() {
+                block.push_str(&format!(
+                    "public struct {}{}: Codable {{}}\n",
+                    name, generics
+                ));
+                w.blocks.push(block);
+                return;
+            }
+            block.push_str(&format!("public struct {}{}: Codable {{\n", name, generics));
+            let mut keys = vec![];
+            let mut renamed = false;
+            d.fields.iter().for_each(|f| {
+                block.push_str(&doc_comment(&f.comments, "    "));
+                let property = to_camel(&f.ident);
+                let ty = field_type(f, names, integers, w);
+                block.push_str(&format!(
+                    "    public var {}: {}\n",
+                    identifier(&property),
+                    ty
+                ));
+                if property == f.ident {
+                    keys.push(format!("        case {}\n", identifier(&property)));
+                } else {
+                    renamed = true;
+                    keys.push(format!(
+                        "        case {} = {}\n",
+                        identifier(&property),
+                        quote(&f.ident)
+                    ));
+                }
+            });
+            // The coding keys are the names after renaming.
+            if renamed {
+                block.push_str("\n    enum CodingKeys: String, CodingKey {\n");
+                keys.iter().for_each(|k| block.push_str(k));
+                block.push_str("    }\n");
+            }
+            block.push_str("}\n");
+            w.blocks.push(block);
+        }
+        Descriptor::Enum(e) => {
+            let name = &names[&(e.file_name.to_string(), e.ts_name.to_string())];
+            if e.fields.iter().all(|f| f.ty.is_none()) {
+                write_string_enum(e, name, w);
+            } else {
+                write_tagged_enum(e, name, names, integers, w);
+            }
+        }
+        _ => {}
+    }
+}
+
+// `models/user-info.ts` -> `models/UserInfo.swift`
+fn swift_file(file_name: &str) -> String {
+    let path = remove_ext(file_name);
+    match path.rsplit_once('/') {
+        Some((dir, stem)) => format!("{}/{}.swift", dir, to_pascal(stem)),
+        None => format!("{}.swift", to_pascal(&path)),
+    }
+}
+
+fn identifier(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn doc_comment(comments: &[String], indent: &str) -> String {
+    comments
+        .iter()
+        .map(|c| format!("{}/// {}\n", indent, c).replace("/// \n", "///\n"))
+        .collect()
+}
+
+// Enums with only unit variants are serialized as their tag values.
+fn write_string_enum(e: &EnumDescriptor, name: &str, w: &mut SwiftWriter) {
+    let mut block = doc_comment(&e.comments, "");
+    block.push_str(&format!("public enum {}: String, Codable {{\n", name));
+    e.fields.iter().for_each(|f| {
+        block.push_str(&doc_comment(&f.comments, "    "));
+        block.push_str(&format!(
+            "    case {} = {}\n",
+            identifier(&to_camel(&f.ident)),
+            quote(&f.tag_value)
+        ));
+    });
+    block.push_str("}\n");
+    w.blocks.push(block);
+}
+
+// Unit variants are the tag values and the others are objects like
+// `{ "type": "guest", "value": 1 }`, so the coding is written by hand.
+fn write_tagged_enum(
+    e: &EnumDescriptor,
+    name: &str,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut SwiftWriter,
+) {
+    let units = e
+        .fields
+        .iter()
+        .filter(|f| f.ty.is_none())
+        .collect::<Vec<_>>();
+    let values = e
+        .fields
+        .iter()
+        .filter_map(|f| {
+            f.ty.as_ref()
+                .map(|ty| (f, type_name(ty, names, integers, w)))
+        })
+        .collect::<Vec<_>>();
+    let case = |f: &FieldDescriptor| identifier(&to_camel(&f.ident));
+    let unknown = |container: &str| {
+        format!(
+            "throw DecodingError.dataCorrupted(.init(codingPath: {}.codingPath, debugDescription: \"unknown {}: \\(tag)\"))",
+            container, name
+        )
+    };
+
+    let mut block = doc_comment(&e.comments, "");
+    block.push_str(&format!("public enum {}: Codable {{\n", name));
+    e.fields.iter().for_each(|f| {
+        block.push_str(&doc_comment(&f.comments, "    "));
+        match values.iter().find(|(v, _)| std::ptr::eq(*v, f)) {
+            Some((_, ty)) => block.push_str(&format!("    case {}({})\n", case(f), ty)),
+            None => block.push_str(&format!("    case {}\n", case(f))),
+        }
+    });
+    block.push_str("\n    private enum CodingKeys: String, CodingKey {\n");
+    block.push_str(&format!("        case tag = {}\n", quote(&e.tag)));
+    block.push_str("        case value\n");
+    block.push_str("    }\n\n");
+
+    block.push_str("    public init(from decoder: Decoder) throws {\n");
+    if !units.is_empty() {
+        block.push_str(
+            "        if let tag = try? decoder.singleValueContainer().decode(String.self) {\n",
+        );
+        block.push_str("            switch tag {\n");
+        units.iter().for_each(|f| {
+            block.push_str(&format!(
+                "            case {}: self = .{}\n",
+                quote(&f.tag_value),
+                case(f)
+            ));
+        });
+        block.push_str(&format!("            default: {}\n", unknown("decoder")));
+        block.push_str("            }\n");
+        block.push_str("            return\n");
+        block.push_str("        }\n");
+    }
+    block.push_str("        let container = try decoder.container(keyedBy: CodingKeys.self)\n");
+    block.push_str("        let tag = try container.decode(String.self, forKey: .tag)\n");
+    block.push_str("        switch tag {\n");
+    values.iter().for_each(|(f, ty)| {
+        block.push_str(&format!(
+            "        case {}: self = .{}(try container.decode({}.self, forKey: .value))\n",
+            quote(&f.tag_value),
+            case(f),
+            ty
+        ));
+    });
+    block.push_str(&format!("        default: {}\n", unknown("container")));
+    block.push_str("        }\n");
+    block.push_str("    }\n\n");
+
+    block.push_str("    public func encode(to encoder: Encoder) throws {\n");
+    block.push_str("        switch self {\n");
+    e.fields.iter().for_each(|f| match &f.ty {
+        None => {
+            block.push_str(&format!("        case .{}:\n", case(f)));
+            block.push_str("            var container = encoder.singleValueContainer()\n");
+            block.push_str(&format!(
+                "            try container.encode({})\n",
+                quote(&f.tag_value)
+            ));
+        }
+        Some(_) => {
+            block.push_str(&format!("        case .{}(let value):\n", case(f)));
+            block.push_str(
+                "            var container = encoder.container(keyedBy: CodingKeys.self)\n",
+            );
+            block.push_str(&format!(
+                "            try container.encode({}, forKey: .tag)\n",
+                quote(&f.tag_value)
+            ));
+            block.push_str("            try container.encode(value, forKey: .value)\n");
+        }
+    });
+    block.push_str("        }\n");
+    block.push_str("    }\n");
+    block.push_str("}\n");
+    w.blocks.push(block);
+}
+
+// Optional fields may be omitted, like `age?: number` in Typescript.
+fn field_type(
+    f: &FieldDescriptor,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut SwiftWriter,
+) -> String {
+    match &f.ty {
+        Some(ty) => type_name(ty, names, integers, w),
+        None => json_value(w),
+    }
+}
+
+fn json_value(w: &mut SwiftWriter) -> String {
+    w.json_value = true;
+    String::from("JSONValue")
+}
+
+fn type_name(
+    ty: &TypeExpr,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+    w: &mut SwiftWriter,
+) -> String {
+    match ty {
+        TypeExpr::Primitive(tag) => {
+            if integers.get(*tag).is_some_and(|t| t == "string") {
+                return String::from("String");
+            }
+            let name = match *tag {
+                "string" => "String",
+                "bool" => "Bool",
+                "f32" => "Float",
+                "f64" => "Double",
+                "u8" => "UInt8",
+                "u16" => "UInt16",
+                "u32" => "UInt32",
+                "u64" => "UInt64",
+                "usize" => "UInt",
+                "i8" => "Int8",
+                "i16" => "Int16",
+                "i32" => "Int32",
+                "i64" => "Int64",
+                _ => "Int",
+            };
+            name.to_string()
+        }
+        TypeExpr::Param(name) => name.to_string(),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name).to_string();
+            if args.is_empty() {
+                return name;
+            }
+            let args = args
+                .iter()
+                .map(|a| type_name(a, names, integers, w))
+                .collect::<Vec<_>>();
+            format!("{}<{}>", name, args.join(", "))
+        }
+        TypeExpr::Array(t) => format!("[{}]", type_name(t, names, integers, w)),
+        TypeExpr::Option(t) => format!("{}?", type_name(t, names, integers, w)),
+        TypeExpr::Map(k, v) => {
+            let k = type_name(k, names, integers, w);
+            let v = type_name(v, names, integers, w);
+            format!("[{}: {}]", k, v)
+        }
+        // Tuples are JSON arrays of mixed types, and the others are not
+        // known by gents.
+        TypeExpr::Result(..) | TypeExpr::Tuple(_) | TypeExpr::Raw(_) => json_value(w),
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use crate::descriptor::{
+    collect_named_types, collect_raw_names, CommandDescriptor, DescriptorManager, TypeExpr,
+};
+use crate::naming::{to_camel, LocalNames, TypeNames};
+use crate::ts_formatter::{ts_type_name, FormatOptions, TsFormatter};
+use crate::typescript::{add_external_imports, add_imports, GeneratedFile};
+
+// Set `invoke` in `external_imports` to import it from another module, like
+// `@tauri-apps/api/tauri` of Tauri 1.
+const INVOKE_MODULE: &str = "@tauri-apps/api/core";
+
+/// Generate the modules of the Tauri commands, which wrap `invoke` in
+/// typed functions like `greetUser(name: string): Promise<string>`.
+pub(crate) fn gen_command_files(
+    manager: &DescriptorManager,
+    options: &FormatOptions,
+    names: &TypeNames,
+) -> Vec<GeneratedFile> {
+    let mut files: Vec<(&str, Vec<&CommandDescriptor>)> = vec![];
+    manager.tauri_commands().iter().for_each(|c| {
+        match files.iter_mut().find(|(f, _)| *f == c.file_name) {
+            Some((_, commands)) => commands.push(c),
+            None => files.push((&c.file_name, vec![c])),
+        }
+    });
+    files
+        .into_iter()
+        .map(|(file_name, commands)| {
+            let types = commands
+                .iter()
+                .flat_map(|c| c.params.iter().map(|(_, t)| t).chain(&c.return_type))
+                .collect::<Vec<_>>();
+            let mut imports = BTreeSet::new();
+            let mut external = BTreeSet::new();
+            types.iter().for_each(|ty| {
+                collect_named_types(ty, &mut imports);
+                collect_raw_names(ty, &mut external);
+            });
+            let imports = imports.into_iter().collect::<Vec<_>>();
+
+            let mut fmt = TsFormatter::new(options);
+            let module = options
+                .external_imports
+                .get("invoke")
+                .map_or(INVOKE_MODULE, |m| m.as_str());
+            fmt.add_external_value_import("invoke", module);
+            let local = add_imports(&mut fmt, names, file_name, &[], &imports, None);
+            add_external_imports(&mut fmt, &external);
+            commands.iter().enumerate().for_each(|(i, c)| {
+                if i > 0 {
+                    fmt.add_blank_line();
+                }
+                write_command(c, &mut fmt, &local);
+            });
+            GeneratedFile::new(file_name, fmt, false)
+        })
+        .collect()
+}
+
+fn write_command(c: &CommandDescriptor, fmt: &mut TsFormatter, names: &LocalNames) {
+    let integers = fmt.options().integer_types.clone();
+    let ts_name = |ty| ts_type_name(ty, false, names, &integers);
+    // The `Option` args can be omitted, but only the trailing ones can be
+    // optional in Typescript.
+    let required = c
+        .params
+        .iter()
+        .rposition(|(_, t)| !matches!(t, TypeExpr::Option(_)))
+        .map_or(0, |i| i + 1);
+    let params = c
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, (n, t))| match t {
+            TypeExpr::Option(_) if i >= required => format!("{}?: {}", n, ts_name(t)),
+            TypeExpr::Option(_) => format!("{}: {} | undefined", n, ts_name(t)),
+            _ => format!("{}: {}", n, ts_name(t)),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let ret = c
+        .return_type
+        .as_ref()
+        .map_or_else(|| String::from("void"), ts_name);
+    let name = to_camel(&c.name);
+    let signature = format!(
+        "export async function {}({}): Promise<{}>",
+        name, params, ret
+    );
+    let command = fmt.quote(&c.name);
+    let call = if c.params.is_empty() {
+        format!("invoke<{}>({})", ret, command)
+    } else {
+        let args = c
+            .params
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("invoke<{}>({}, {{ {} }})", ret, command, args)
+    };
+    fmt.add_comment(&c.comment);
+    fmt.start_function(&name, &signature);
+    fmt.add_method_line(&format!("return {}", call));
+    fmt.end_method();
+}
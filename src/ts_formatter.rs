@@ -1,31 +1,259 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use crate::descriptor::{DeclKind, TypeExpr};
+use crate::naming::{LocalNames, NameCollision};
+
+// Import statements longer than this will be broken into multiple lines.
+const PRINT_WIDTH: usize = 80;
+
+/// Options to control the style of the generated Typescript code.
+///
+/// The default options keep the original style of gents: four-space
+/// indentation, no semicolons, single quotes and LF line endings.
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    pub semicolons: bool,
+    pub quote: QuoteStyle,
+    // Append a comma to the last item of multi-line lists, like
+    // the wrapped import statements.
+    pub trailing_comma: bool,
+    pub line_ending: LineEnding,
+    // Mark all the interface fields as `readonly` and use the readonly
+    // versions of collections, like `ReadonlyMap`.
+    pub readonly_fields: bool,
+    // The default way to declare the object types. It can be overridden by
+    // `#[ts(decl = "...")]` on the containers.
+    pub decl: DeclKind,
+    // Use `import type { A } from './a'`, which is required by
+    // `verbatimModuleSyntax` and `isolatedModules`.
+    pub type_imports: bool,
+    // The extension appended to the import specifiers, e.g. `.js` is
+    // required by the `NodeNext` module resolution.
+    pub import_extension: ImportExtension,
+    // What to do with the types sharing the same name or shadowing the
+    // builtin types of Typescript.
+    pub name_collision: NameCollision,
+    // Added to the names of all the generated types, e.g. `Api` for `ApiUser`.
+    pub type_prefix: String,
+    pub type_suffix: String,
+    // The Typescript types of the integers by their Rust names, like `u64` -> `bigint`.
+    // Integers not in it are `number`.
+    pub integer_types: BTreeMap<String, String>,
+    // Types not generated by gents and the modules to import them from, like
+    // `Dayjs` -> `dayjs`.
+    pub external_imports: BTreeMap<String, String>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: Indent::Spaces(4),
+            semicolons: false,
+            quote: QuoteStyle::Single,
+            trailing_comma: false,
+            line_ending: LineEnding::Lf,
+            readonly_fields: false,
+            decl: DeclKind::Interface,
+            type_imports: false,
+            import_extension: ImportExtension::None,
+            name_collision: NameCollision::Ignore,
+            type_prefix: String::new(),
+            type_suffix: String::new(),
+            integer_types: BTreeMap::new(),
+            external_imports: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    Single,
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportExtension {
+    None,
+    Js,
+    Ts,
+}
+
+impl ImportExtension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportExtension::None => "",
+            ImportExtension::Js => ".js",
+            ImportExtension::Ts => ".ts",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    Lf,
+    #[serde(rename = "crlf")]
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct TsFormatter {
-    imports: BTreeSet<String>,
+    options: FormatOptions,
+    // module path -> imported names
+    imports: BTreeMap<String, BTreeSet<String>>,
+    // module path -> imported names which exist at runtime, like the schemas
+    value_imports: BTreeMap<String, BTreeSet<String>>,
     lines: Vec<String>,
     indent: usize,
+    // names of the exported types
+    types: Vec<String>,
+    // names of the exported values which exist at runtime, like builders
+    values: Vec<String>,
     // enum building state
     enum_mode: bool,
     enum_variants: Vec<String>,
 }
 
 impl TsFormatter {
-    pub fn new() -> Self {
+    pub fn new(options: &FormatOptions) -> Self {
         Self {
-            imports: BTreeSet::new(),
+            options: options.clone(),
+            imports: BTreeMap::new(),
+            value_imports: BTreeMap::new(),
             lines: vec![],
             indent: 0,
+            types: vec![],
+            values: vec![],
             enum_mode: false,
             enum_variants: vec![],
         }
     }
 
-    pub fn add_import(&mut self, ts_name: &str, file_name_no_ext: &str) {
-        self.imports.insert(format!(
-            "import {{ {} }} from './{}'",
-            ts_name, file_name_no_ext
-        ));
+    pub fn options(&self) -> &FormatOptions {
+        &self.options
+    }
+
+    pub fn exported_types(&self) -> &[String] {
+        &self.types
+    }
+
+    pub fn exported_values(&self) -> &[String] {
+        &self.values
+    }
+
+    // Import the type with an optional alias, like `import { Error as DbError } from './db'`.
+    pub fn add_import(&mut self, ts_name: &str, alias: Option<&str>, file_name_no_ext: &str) {
+        let path = self.module_specifier(file_name_no_ext);
+        let name = match alias {
+            Some(alias) => format!("{} as {}", ts_name, alias),
+            None => ts_name.to_string(),
+        };
+        self.imports.entry(path).or_default().insert(name);
+    }
+
+    // Import a value, which is never imported by `import type`.
+    pub fn add_value_import(&mut self, name: &str, alias: Option<&str>, file_name_no_ext: &str) {
+        let path = self.module_specifier(file_name_no_ext);
+        let name = match alias {
+            Some(alias) => format!("{} as {}", name, alias),
+            None => name.to_string(),
+        };
+        self.value_imports.entry(path).or_default().insert(name);
+    }
+
+    // Import the type from a package, like `import { Dayjs } from 'dayjs'`.
+    pub fn add_external_import(&mut self, ts_name: &str, module: &str) {
+        self.imports
+            .entry(module.to_string())
+            .or_default()
+            .insert(ts_name.to_string());
+    }
+
+    // Import a value from a package, like `import { z } from 'zod'`.
+    pub fn add_external_value_import(&mut self, name: &str, module: &str) {
+        self.value_imports
+            .entry(module.to_string())
+            .or_default()
+            .insert(name.to_string());
+    }
+
+    // Re-export everything from the file. Files without runtime code are
+    // re-exported as types when using type imports.
+    pub fn add_export_all(&mut self, file_name_no_ext: &str, runtime: bool) {
+        let path = self.quote(&self.module_specifier(file_name_no_ext));
+        let export = if self.options.type_imports && !runtime {
+            "export type *"
+        } else {
+            "export *"
+        };
+        let line = self.statement(&format!("{} from {}", export, path));
+        self.write_line(&line);
+    }
+
+    // Re-export the names from the file, like `export { A, ABuilder } from './a'`.
+    pub fn add_export_names(
+        &mut self,
+        file_name_no_ext: &str,
+        types: &[String],
+        values: &[String],
+    ) {
+        let path = self.module_specifier(file_name_no_ext);
+        let (type_only, names) = self.mixed_names(types, values);
+        let export = if type_only { "export type" } else { "export" };
+        let line = self.named_statement(export, &names, &path);
+        self.write_line(&line);
+    }
+
+    // The names of the types and the values in one statement. Types are
+    // marked by `type` when using type imports, unless there is no value.
+    fn mixed_names(&self, types: &[String], values: &[String]) -> (bool, Vec<String>) {
+        let type_only = self.options.type_imports && values.is_empty();
+        let mut names = types
+            .iter()
+            .map(|t| {
+                if self.options.type_imports && !type_only {
+                    format!("type {}", t)
+                } else {
+                    t.to_string()
+                }
+            })
+            .chain(values.iter().cloned())
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| {
+            a.trim_start_matches("type ")
+                .cmp(b.trim_start_matches("type "))
+        });
+        (type_only, names)
+    }
+
+    /// Quote a string literal with the quote style in the options.
+    pub fn quote(&self, s: &str) -> String {
+        let q = match self.options.quote {
+            QuoteStyle::Single => '\'',
+            QuoteStyle::Double => '"',
+        };
+        let escaped = s.replace('\\', "\\\\").replace(q, &format!("\\{}", q));
+        format!("{}{}{}", q, escaped, q)
     }
 
     pub fn add_blank_line(&mut self) {
@@ -45,22 +273,73 @@ impl TsFormatter {
         }
     }
 
+    pub fn start_namespace(&mut self, name: &str) {
+        self.write_line(&format!("declare namespace {} {{", name));
+        self.indent += 1;
+    }
+
+    pub fn end_namespace(&mut self) {
+        if self.indent > 0 {
+            self.indent -= 1;
+        }
+        self.write_line("}");
+    }
+
     pub fn start_interface(&mut self, name: &str, generics: &str) {
+        self.types.push(name.to_string());
         self.write_line(&format!("export interface {}{} {{", name, generics));
         self.indent += 1;
     }
 
-    pub fn add_field(&mut self, name: &str, ty: &str, optional: bool, comments: &[String]) {
+    pub fn start_type_alias(&mut self, name: &str, generics: &str) {
+        self.types.push(name.to_string());
+        self.write_line(&format!("export type {}{} = {{", name, generics));
+        self.indent += 1;
+    }
+
+    pub fn end_type_alias(&mut self) {
+        if self.indent > 0 {
+            self.indent -= 1;
+        }
+        let line = self.statement("}");
+        self.write_line(&line);
+    }
+
+    pub fn start_declaration(&mut self, decl: DeclKind, name: &str, generics: &str) {
+        match decl {
+            DeclKind::Interface => self.start_interface(name, generics),
+            DeclKind::TypeAlias => self.start_type_alias(name, generics),
+        }
+    }
+
+    pub fn end_declaration(&mut self, decl: DeclKind) {
+        match decl {
+            DeclKind::Interface => self.end_interface(),
+            DeclKind::TypeAlias => self.end_type_alias(),
+        }
+    }
+
+    pub fn add_field(
+        &mut self,
+        name: &str,
+        ty: &str,
+        optional: bool,
+        readonly: bool,
+        comments: &[String],
+    ) {
         if !comments.is_empty() {
             for c in comments {
                 self.write_line(&format!("// {}", c));
             }
         }
-        if optional {
-            self.write_line(&format!("{}?: {}", name, ty));
+        let modifier = if readonly { "readonly " } else { "" };
+        let line = if optional {
+            format!("{}{}?: {}", modifier, name, ty)
         } else {
-            self.write_line(&format!("{}: {}", name, ty));
-        }
+            format!("{}{}: {}", modifier, name, ty)
+        };
+        let line = self.statement(&line);
+        self.write_line(&line);
     }
 
     pub fn add_method(&mut self, name: &str, params: Vec<(String, String)>, ret: Option<String>) {
@@ -70,7 +349,8 @@ impl TsFormatter {
             .collect::<Vec<_>>()
             .join(", ");
         let ret_str = ret.map_or("void".to_string(), |r| r);
-        self.write_line(&format!("{}({}): {};", name, param_str, ret_str));
+        let line = self.statement(&format!("{}({}): {}", name, param_str, ret_str));
+        self.write_line(&line);
     }
 
     pub fn add_async_method(
@@ -85,10 +365,11 @@ impl TsFormatter {
             .collect::<Vec<_>>()
             .join(", ");
         let ret_str = ret.map_or("void".to_string(), |r| r);
-        self.write_line(&format!(
-            "async {}({}): Promise<{}>;",
+        let line = self.statement(&format!(
+            "async {}({}): Promise<{}>",
             name, param_str, ret_str
         ));
+        self.write_line(&line);
     }
 
     pub fn end_interface(&mut self) {
@@ -100,13 +381,38 @@ impl TsFormatter {
 
     // Class and methods helpers
     pub fn start_class(&mut self, name: &str) {
-        self.write_line(&format!("export class {} {{", name));
+        self.start_class_with(name, "");
+    }
+
+    // Start a class with `extends` or `implements`, like `implements A`.
+    pub fn start_class_with(&mut self, name: &str, heritage: &str) {
+        self.values.push(name.to_string());
+        if heritage.is_empty() {
+            self.write_line(&format!("export class {} {{", name));
+        } else {
+            self.write_line(&format!("export class {} {} {{", name, heritage));
+        }
         self.indent += 1;
     }
 
+    // A one-line type alias like `export type A = (x: B) => C`.
+    pub fn add_type_alias(&mut self, name: &str, ty: &str) {
+        self.types.push(name.to_string());
+        let line = self.statement(&format!("export type {} = {}", name, ty));
+        self.write_line(&line);
+    }
+
     pub fn add_class_field(&mut self, decl: &str) {
         // decl like: "private _f1!: number" (no semicolon)
-        self.write_line(decl);
+        let line = self.statement(decl);
+        self.write_line(&line);
+    }
+
+    // Start an exported function like `export function isA(x: unknown): x is A {`.
+    // It is ended by `end_method`.
+    pub fn start_function(&mut self, name: &str, signature: &str) {
+        self.values.push(name.to_string());
+        self.start_method(signature);
     }
 
     pub fn start_method(&mut self, signature: &str) {
@@ -116,7 +422,9 @@ impl TsFormatter {
     }
 
     pub fn add_method_line(&mut self, line: &str) {
-        self.write_line(line);
+        // line like: "return this" (no semicolon)
+        let line = self.statement(line);
+        self.write_line(&line);
     }
 
     pub fn end_method(&mut self) {
@@ -133,7 +441,34 @@ impl TsFormatter {
         self.write_line("}");
     }
 
+    // Start a block of a value like `export const ASchema = z.object({`.
+    pub fn start_value_block(&mut self, name: &str, head: &str) {
+        self.values.push(name.to_string());
+        self.write_line(head);
+        self.indent += 1;
+    }
+
+    // An item of a multi-line list, followed by a comma unless it is the last
+    // one and trailing commas are not used.
+    pub fn add_list_item(&mut self, item: &str, last: bool) {
+        if last && !self.options.trailing_comma {
+            self.write_line(item);
+        } else {
+            self.write_line(&format!("{},", item));
+        }
+    }
+
+    // End the block with the closing brackets like `})`.
+    pub fn end_value_block(&mut self, tail: &str) {
+        if self.indent > 0 {
+            self.indent -= 1;
+        }
+        let line = self.statement(tail);
+        self.write_line(&line);
+    }
+
     pub fn start_enum(&mut self, name: &str) {
+        self.types.push(name.to_string());
         self.enum_mode = true;
         self.enum_variants.clear();
         self.write_line(&format!("export type {} =", name));
@@ -148,42 +483,187 @@ impl TsFormatter {
     pub fn end_enum(&mut self) {
         if self.enum_mode {
             let indent = self.current_indent_string(1);
-            for v in self.enum_variants.iter() {
+            let variants = std::mem::take(&mut self.enum_variants);
+            let last = variants.len().saturating_sub(1);
+            for (i, v) in variants.iter().enumerate() {
+                let v = if i == last {
+                    self.statement(v)
+                } else {
+                    v.to_string()
+                };
                 self.lines.push(format!("{}| {}", indent, v));
             }
             self.enum_mode = false;
-            self.enum_variants.clear();
         }
     }
 
     pub fn end_file(self) -> String {
+        let newline = self.options.line_ending.as_str();
         let mut out = String::new();
-        if !self.imports.is_empty() {
-            for i in &self.imports {
-                out.push_str(i);
-                out.push('\n');
+        let paths = self
+            .imports
+            .keys()
+            .chain(self.value_imports.keys())
+            .collect::<BTreeSet<_>>();
+        if !paths.is_empty() {
+            for path in paths {
+                let collect = |m: &BTreeMap<String, BTreeSet<String>>| {
+                    m.get(path)
+                        .map(|names| names.iter().cloned().collect::<Vec<_>>())
+                        .unwrap_or_default()
+                };
+                let (type_only, names) =
+                    self.mixed_names(&collect(&self.imports), &collect(&self.value_imports));
+                let import = if type_only { "import type" } else { "import" };
+                out.push_str(&self.named_statement(import, &names, path));
+                out.push_str(newline);
             }
-            out.push('\n');
+            out.push_str(newline);
         }
-        for l in self.lines {
-            if l.is_empty() {
-                out.push('\n');
-            } else {
-                out.push_str(&l);
-                // no semicolons; rely on ASI style
-                out.push('\n');
-            }
+        for l in &self.lines {
+            out.push_str(l);
+            out.push_str(newline);
         }
         out
     }
 
+    // Statements like `import { A, B } from './a'`. They will be broken into
+    // multiple lines if they are too long.
+    fn named_statement(&self, keyword: &str, names: &[String], path: &str) -> String {
+        let path = self.quote(path);
+        let line = self.statement(&format!(
+            "{} {{ {} }} from {}",
+            keyword,
+            names.join(", "),
+            path
+        ));
+        if line.len() <= PRINT_WIDTH || names.len() == 1 {
+            return line;
+        }
+        let newline = self.options.line_ending.as_str();
+        let indent = self.indent_unit();
+        let last = names.len() - 1;
+        let items = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                if i < last || self.options.trailing_comma {
+                    format!("{}{},{}", indent, n, newline)
+                } else {
+                    format!("{}{}{}", indent, n, newline)
+                }
+            })
+            .collect::<String>();
+        self.statement(&format!(
+            "{} {{{}{}}} from {}",
+            keyword, newline, items, path
+        ))
+    }
+
+    // The paths are relative to the current file, like `../models/user`. The
+    // paths of the sibling files can omit the leading `./`.
+    fn module_specifier(&self, file_name_no_ext: &str) -> String {
+        let ext = self.options.import_extension.as_str();
+        if file_name_no_ext.starts_with("./") || file_name_no_ext.starts_with("../") {
+            format!("{}{}", file_name_no_ext, ext)
+        } else {
+            format!("./{}{}", file_name_no_ext, ext)
+        }
+    }
+
+    // Append a semicolon if needed.
+    fn statement(&self, s: &str) -> String {
+        if self.options.semicolons {
+            format!("{};", s)
+        } else {
+            s.to_string()
+        }
+    }
+
     fn write_line(&mut self, content: &str) {
         let indent = self.current_indent_string(0);
         self.lines.push(format!("{}{}", indent, content));
     }
 
+    fn indent_unit(&self) -> String {
+        match self.options.indent {
+            Indent::Spaces(n) => " ".repeat(n),
+            Indent::Tab => String::from("\t"),
+        }
+    }
+
     fn current_indent_string(&self, extra: usize) -> String {
         let n = self.indent + extra;
-        "    ".repeat(n)
+        self.indent_unit().repeat(n)
+    }
+}
+
+/// Get the Typescript name of the type. Collections will be mapped to their
+/// readonly versions if `readonly` is true.
+pub fn ts_type_name(
+    ty: &TypeExpr,
+    readonly: bool,
+    names: &LocalNames,
+    integers: &BTreeMap<String, String>,
+) -> String {
+    match ty {
+        TypeExpr::Primitive(tag) => match *tag {
+            "string" => String::from("string"),
+            "bool" => String::from("boolean"),
+            _ => integers
+                .get(*tag)
+                .cloned()
+                .unwrap_or_else(|| String::from("number")),
+        },
+        TypeExpr::Param(name) => name.to_string(),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name);
+            if args.is_empty() {
+                return name.to_string();
+            }
+            let args = args
+                .iter()
+                .map(|a| ts_type_name(a, readonly, names, integers))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{}<{}>", name, args)
+        }
+        TypeExpr::Array(t) => {
+            if let TypeExpr::Primitive("u8") = t.as_ref() {
+                return String::from("Uint8Array");
+            }
+            format!("readonly {}[]", ts_type_name(t, readonly, names, integers))
+        }
+        TypeExpr::Option(t) => ts_type_name(t, readonly, names, integers),
+        TypeExpr::Result(t, e) => {
+            format!(
+                "{} | {}",
+                ts_type_name(t, readonly, names, integers),
+                ts_type_name(e, readonly, names, integers)
+            )
+        }
+        TypeExpr::Tuple(items) => {
+            let items = items
+                .iter()
+                .map(|i| ts_type_name(i, readonly, names, integers))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("(readonly [{}])", items)
+        }
+        TypeExpr::Map(k, v) => {
+            let map = if readonly { "ReadonlyMap" } else { "Map" };
+            format!(
+                "{}<{}, {}>",
+                map,
+                ts_type_name(k, readonly, names, integers),
+                ts_type_name(v, readonly, names, integers)
+            )
+        }
+        TypeExpr::Raw(s) => s.to_string(),
     }
 }
@@ -1,3 +1,40 @@
 pub fn remove_ext(s: &str) -> String {
     s.strip_suffix(".ts").unwrap_or(s).to_string()
 }
+
+// The path of `to` relative to the directory of the file `from`.
+// `models/user.ts` and `models/auth/token` -> `./auth/token`
+// `models/user.ts` and `db` -> `../db`
+pub fn relative_path(from: &str, to: &str) -> String {
+    let from_dir = from.split('/').collect::<Vec<_>>();
+    let from_dir = &from_dir[..from_dir.len() - 1];
+    let to = to.split('/').collect::<Vec<_>>();
+    let common = from_dir
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let ups = from_dir.len() - common;
+    let rest = to[common..].join("/");
+    if ups == 0 {
+        format!("./{}", rest)
+    } else {
+        format!("{}{}", "../".repeat(ups), rest)
+    }
+}
+
+// `UserProfile` -> `user_profile`
+pub fn to_snake_case(s: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
@@ -0,0 +1,66 @@
+use crate::backend::Backend;
+use crate::descriptor::DescriptorManager;
+use crate::file_generator::PREFIX;
+use crate::ts_formatter::FormatOptions;
+use crate::typescript::TypescriptBackend;
+
+/// A Rust file declaring the types and the APIs in a `typescript_custom_section`
+/// of wasm-bindgen, `gents_ts.rs` by default.
+///
+/// Generate it into the `src` of the wasm crate and add `mod gents_ts;`, then
+/// the `.d.ts` of wasm-bindgen declares the gents types, and the classes of
+/// `#[ts_interface(wasm)]` get the signatures of their API interfaces.
+///
+/// The file uses wasm-bindgen through `::gents::wasm_bindgen`, so enable the
+/// `wasm` feature of gents.
+#[derive(Debug, Clone)]
+pub struct WasmBindgenBackend {
+    pub options: FormatOptions,
+    pub file_name: String,
+}
+
+impl Default for WasmBindgenBackend {
+    fn default() -> Self {
+        WasmBindgenBackend {
+            options: FormatOptions::default(),
+            file_name: String::from("gents_ts.rs"),
+        }
+    }
+}
+
+impl Backend for WasmBindgenBackend {
+    // All the types and the APIs are declared in the section, so that the
+    // `.d.ts` of wasm-bindgen declares them as well.
+    //
+    // The runtime code like builders is skipped since the section is a
+    // declaration file.
+    fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+        let ts = TypescriptBackend {
+            options: self.options.clone(),
+            ..Default::default()
+        };
+        let content = ts.gen_bundle(manager, None, true);
+        // The raw string must not be closed by the content.
+        let mut hashes = String::from("#");
+        while content.contains(&format!("\"{}", hashes)) {
+            hashes.push('#');
+        }
+        let newline = self.options.line_ending.as_str();
+        let content = format!(
+            "{prefix}{nl}#[::gents::wasm_bindgen::prelude::wasm_bindgen({nl}    \
+             wasm_bindgen = ::gents::wasm_bindgen,{nl}    \
+             typescript_custom_section,{nl}\
+             )]{nl}\
+             const GENTS_TS: &'static str = r{h}\"{nl}{content}\"{h};{nl}",
+            prefix = PREFIX,
+            nl = newline,
+            h = hashes,
+            content = content,
+        );
+        vec![(self.file_name.to_string(), content)]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rs"]
+    }
+}
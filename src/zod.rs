@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use crate::descriptor::{EnumDescriptor, FieldDescriptor, InterfaceDescriptor, TypeExpr};
+use crate::naming::{LocalNames, TypeKey};
+use crate::ts_formatter::TsFormatter;
+
+// `User` -> `UserSchema`
+pub(crate) fn schema_name(name: &str) -> String {
+    format!("{}Schema", name)
+}
+
+// The type of the data parsed by the schema, like
+// `export type UserInput = z.infer<typeof UserSchema>`.
+fn write_infer_type(name: &str, fmt: &mut TsFormatter) {
+    let ty = format!("z.infer<typeof {}>", schema_name(name));
+    fmt.add_type_alias(&format!("{}Input", name), &ty);
+}
+
+// The parameters of the generic schemas, like
+// `<T extends z.ZodTypeAny>(TSchema: T) =>`.
+fn generic_head(generics: &[String]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+    let params = generics
+        .iter()
+        .map(|g| format!("{} extends z.ZodTypeAny", g))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let args = generics
+        .iter()
+        .map(|g| format!("{}: {}", schema_name(g), g))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("<{}>({}) => ", params, args)
+}
+
+pub(crate) fn write_interface_schema(
+    d: &InterfaceDescriptor,
+    name: &str,
+    generics: &[String],
+    fmt: &mut TsFormatter,
+    names: &LocalNames,
+    own: &[TypeKey],
+) {
+    let schema = schema_name(name);
+    let head = format!(
+        "export const {} = {}z.object({{",
+        schema,
+        generic_head(generics)
+    );
+    fmt.start_value_block(&schema, &head);
+    let integers = fmt.options().integer_types.clone();
+    let last = d.fields.len().saturating_sub(1);
+    d.fields.iter().enumerate().for_each(|(i, fd)| {
+        let ty = field_schema(fd, names, own, &integers);
+        fmt.add_list_item(&format!("{}: {}", fd.ident, ty), i == last);
+    });
+    fmt.end_value_block("})");
+    // The generic schemas are functions, whose types depend on the arguments.
+    if generics.is_empty() {
+        write_infer_type(name, fmt);
+    }
+}
+
+// Unit variants are literals and the others are objects discriminated by
+// the tag. Enums with only unit variants are `z.enum`.
+pub(crate) fn write_enum_schema(
+    e: &EnumDescriptor,
+    name: &str,
+    fmt: &mut TsFormatter,
+    names: &LocalNames,
+    own: &[TypeKey],
+) {
+    let schema = schema_name(name);
+    let integers = fmt.options().integer_types.clone();
+    let last = e.fields.len().saturating_sub(1);
+    if e.fields.iter().all(|f| f.ty.is_none()) {
+        fmt.start_value_block(&schema, &format!("export const {} = z.enum([", schema));
+        e.fields.iter().enumerate().for_each(|(i, fd)| {
+            let value = fmt.quote(&fd.tag_value);
+            fmt.add_list_item(&value, i == last);
+        });
+        fmt.end_value_block("])");
+        write_infer_type(name, fmt);
+        return;
+    }
+    let head = if e.fields.iter().all(|f| f.ty.is_some()) {
+        format!(
+            "export const {} = z.discriminatedUnion({}, [",
+            schema,
+            fmt.quote(&e.tag)
+        )
+    } else {
+        format!("export const {} = z.union([", schema)
+    };
+    fmt.start_value_block(&schema, &head);
+    e.fields.iter().enumerate().for_each(|(i, fd)| {
+        let literal = format!("z.literal({})", fmt.quote(&fd.tag_value));
+        let item = match &fd.ty {
+            None => literal,
+            Some(ty) => format!(
+                "z.object({{ {}: {}, value: {} }})",
+                e.tag,
+                literal,
+                type_schema(ty, names, own, &integers)
+            ),
+        };
+        fmt.add_list_item(&item, i == last);
+    });
+    fmt.end_value_block("])");
+    write_infer_type(name, fmt);
+}
+
+fn field_schema(
+    fd: &FieldDescriptor,
+    names: &LocalNames,
+    own: &[TypeKey],
+    integers: &BTreeMap<String, String>,
+) -> String {
+    match &fd.ty {
+        Some(ty) => type_schema(ty, names, own, integers),
+        None => String::from("z.unknown()"),
+    }
+}
+
+/// Get the zod schema of the type. The types declared in the same file are
+/// referred lazily, since they may be declared later or recursive.
+fn type_schema(
+    ty: &TypeExpr,
+    names: &LocalNames,
+    own: &[TypeKey],
+    integers: &BTreeMap<String, String>,
+) -> String {
+    let schema = |t: &TypeExpr| type_schema(t, names, own, integers);
+    match ty {
+        TypeExpr::Primitive(tag) => match *tag {
+            "string" => String::from("z.string()"),
+            "bool" => String::from("z.boolean()"),
+            _ => match integers.get(*tag).map(|s| s.as_str()) {
+                None | Some("number") => String::from("z.number()"),
+                Some("bigint") => String::from("z.bigint()"),
+                Some("string") => String::from("z.string()"),
+                Some(t) => format!("z.custom<{}>()", t),
+            },
+        },
+        TypeExpr::Param(name) => schema_name(name),
+        TypeExpr::Named {
+            name,
+            file_name,
+            args,
+        } => {
+            let key = (file_name.to_string(), name.to_string());
+            let name = names.get(&key).unwrap_or(name);
+            let mut result = schema_name(name);
+            if !args.is_empty() {
+                let args = args.iter().map(schema).collect::<Vec<_>>().join(", ");
+                result = format!("{}({})", result, args);
+            }
+            if own.contains(&key) {
+                format!("z.lazy(() => {})", result)
+            } else {
+                result
+            }
+        }
+        TypeExpr::Array(t) => {
+            if let TypeExpr::Primitive("u8") = t.as_ref() {
+                return String::from("z.instanceof(Uint8Array)");
+            }
+            format!("z.array({})", schema(t))
+        }
+        TypeExpr::Option(t) => format!("{}.optional()", schema(t)),
+        TypeExpr::Result(t, e) => format!("z.union([{}, {}])", schema(t), schema(e)),
+        TypeExpr::Tuple(items) => {
+            let items = items.iter().map(schema).collect::<Vec<_>>().join(", ");
+            format!("z.tuple([{}])", items)
+        }
+        // The maps are plain objects in JSON, whose keys are strings.
+        TypeExpr::Map(k, v) => {
+            let key = match k.as_ref() {
+                TypeExpr::Primitive("bool") => String::from("z.string()"),
+                // Parse the numbers from the keys.
+                TypeExpr::Primitive(_) => match schema(k).as_str() {
+                    "z.number()" => String::from("z.coerce.number()"),
+                    "z.bigint()" => String::from("z.coerce.bigint()"),
+                    key => key.to_string(),
+                },
+                k => schema(k),
+            };
+            format!("z.record({}, {})", key, schema(v))
+        }
+        TypeExpr::Raw(name) => format!("z.custom<{}>()", name),
+    }
+}
@@ -64,6 +64,41 @@ mod tests {
         )
     }
 
+    #[test]
+    fn gen_std_types_test() {
+        use std::collections::{BTreeMap, HashSet, VecDeque};
+        use std::sync::Arc;
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "std_types.ts", rename_all = "camelCase")]
+        pub struct StdTypes {
+            pub small: i16,
+            pub size: isize,
+            pub tags: HashSet<String>,
+            pub queue: VecDeque<u32>,
+            pub scores: BTreeMap<String, u8>,
+            pub owner: Box<Person>,
+        }
+
+        let mut manager = DescriptorManager::default();
+        StdTypes::_register(&mut manager, true);
+        let (_, content) = manager.gen_data().into_iter().last().unwrap();
+        assert_eq!(
+            content.trim(),
+            r#"import { Person } from './person'
+
+export interface StdTypes {
+    small: number
+    size: number
+    tags: readonly string[]
+    queue: readonly number[]
+    scores: Map<string, number>
+    owner: Person
+}"#
+        );
+        assert_eq!(<Arc<Person>>::_ts_name(), "Person");
+    }
+
     #[test]
     fn gen_data_person_test() {
         let mut manager = DescriptorManager::default();
@@ -264,8 +299,11 @@ export class VariantBuilder {
         if (this._f2 === undefined) throw new Error('missing f2')
         return { f1: this._f1, f2: this._f2 }
     }
-}"#
-            .trim()
+}
+
+export type TaggedEnum =
+    | { type: 'variant'; value: Variant }"#
+                .trim()
         );
     }
 
@@ -420,6 +458,181 @@ export interface V3 {
         );
     }
 
+    #[test]
+    fn test_format_options() {
+        #[derive(TS, Clone)]
+        #[ts(file_name = "a.ts", rename_all = "camelCase")]
+        #[ts(builder)]
+        pub struct V1 {
+            pub f1: u8,
+            pub f2: Option<Gender>,
+        }
+
+        let options = FormatOptions {
+            indent: Indent::Spaces(2),
+            semicolons: true,
+            quote: QuoteStyle::Double,
+            trailing_comma: true,
+            line_ending: LineEnding::Lf,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        V1::_register(&mut manager, true);
+        let data = manager.gen_data_with_options(&options);
+        let files: std::collections::HashMap<_, _> = data.into_iter().collect();
+        assert_eq!(
+            files.get("a.ts").unwrap(),
+            r#"import { Gender } from "./gender";
+
+export interface V1 {
+  f1: number;
+  f2?: Gender;
+}
+
+export class V1Builder {
+  private _f1!: number;
+  private _f2?: Gender;
+  public f1(value: number) {
+    this._f1 = value;
+    return this;
+  }
+
+  public f2(value: Gender) {
+    this._f2 = value;
+    return this;
+  }
+  public build() {
+    if (this._f1 === undefined) throw new Error("missing f1");
+    return { f1: this._f1, f2: this._f2 };
+  }
+}
+"#
+        );
+        assert_eq!(
+            files.get("gender.ts").unwrap(),
+            "export type Gender =\n  | \"male\"\n  | \"female\"\n  | \"null\";\n"
+        );
+    }
+
+    #[test]
+    fn test_readonly() {
+        #[derive(TS, Clone)]
+        #[ts(file_name = "a.ts", rename_all = "camelCase")]
+        pub struct V1 {
+            #[ts(readonly)]
+            pub f1: std::collections::HashMap<String, u8>,
+            pub f2: std::collections::HashMap<String, u8>,
+        }
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "b.ts", rename_all = "camelCase", readonly)]
+        pub struct V2 {
+            pub f1: Vec<u32>,
+        }
+
+        let mut manager = DescriptorManager::default();
+        V1::_register(&mut manager, true);
+        V2::_register(&mut manager, true);
+        let data = manager.gen_data();
+        assert_eq!(
+            data[0].1.trim(),
+            r#"export interface V1 {
+    readonly f1: ReadonlyMap<string, number>
+    f2: Map<string, number>
+}"#
+        );
+        assert_eq!(
+            data[1].1.trim(),
+            r#"export interface V2 {
+    readonly f1: readonly number[]
+}"#
+        );
+
+        let options = FormatOptions {
+            readonly_fields: true,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        V1::_register(&mut manager, true);
+        let (_, content) = manager
+            .gen_data_with_options(&options)
+            .into_iter()
+            .next()
+            .unwrap();
+        assert_eq!(
+            content.trim(),
+            r#"export interface V1 {
+    readonly f1: ReadonlyMap<string, number>
+    readonly f2: ReadonlyMap<string, number>
+}"#
+        );
+    }
+
+    #[test]
+    fn test_type_alias() {
+        #[derive(TS, Clone)]
+        #[ts(file_name = "a.ts", rename_all = "camelCase", decl = "type")]
+        pub struct V1 {
+            pub f1: u8,
+        }
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "b.ts", rename_all = "camelCase", decl = "interface")]
+        pub struct V2 {
+            pub f1: u8,
+        }
+
+        let mut manager = DescriptorManager::default();
+        V1::_register(&mut manager, true);
+        let (_, content) = manager.gen_data().into_iter().next().unwrap();
+        assert_eq!(
+            content.trim(),
+            r#"export type V1 = {
+    f1: number
+}"#
+        );
+
+        let options = FormatOptions {
+            decl: DeclKind::TypeAlias,
+            semicolons: true,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        Person::_register(&mut manager, true);
+        V2::_register(&mut manager, true);
+        let data = manager.gen_data_with_options(&options);
+        assert_eq!(
+            data[0].1.trim(),
+            r#"export type Person = {
+    age: number;
+    enName: string;
+};"#
+        );
+        assert_eq!(
+            data[1].1.trim(),
+            r#"export interface V2 {
+    f1: number;
+}"#
+        );
+    }
+
+    #[test]
+    fn test_type_imports() {
+        let options = FormatOptions {
+            type_imports: true,
+            import_extension: ImportExtension::Js,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        Group::_register(&mut manager, true);
+        let (_, content) = manager
+            .gen_data_with_options(&options)
+            .into_iter()
+            .last()
+            .unwrap();
+        assert!(content.starts_with("import type { Person } from './person.js'\n"));
+    }
+
     #[test]
     fn test_multiple_tags() {
         #[derive(TS, Clone)]
@@ -457,7 +670,21 @@ export interface V3 {
         A::_register(&mut manager, true);
         B::_register(&mut manager, true);
         let data = manager.gen_data();
-        assert_eq!(data.len(), 4);
+        // Types sharing the same file are generated together.
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].0, "a.ts");
+        assert!(
+            data[0]
+                .1
+                .starts_with("import { V2 } from './b'\n\nexport interface V1 {")
+        );
+        assert!(data[0].1.contains("export type A ="));
+        assert!(
+            data[1]
+                .1
+                .starts_with("import { V1 } from './a'\n\nexport interface V2 {")
+        );
+        assert!(data[1].1.contains("export type B ="));
     }
 }
 
@@ -510,7 +737,2623 @@ mod test_api {
 
         assert_eq!(
             files.get("v1_api.ts").unwrap(),
-            &"export interface V1Api {\n    f1(): number;\n    f2(): string;\n    // set f1\n    setF1(f1: number): void;\n    setF2(f2: string): void;\n}\n"
+            &"export interface V1Api {\n    f1(): number\n    f2(): string\n    // set f1\n    setF1(f1: number): void\n    setF2(f2: string): void\n}\n"
+        );
+
+        let mut manager = DescriptorManager::default();
+        manager.add_api_descriptor(V1::__get_api_descriptor());
+        let options = FormatOptions {
+            semicolons: true,
+            ..FormatOptions::default()
+        };
+        let data = manager.gen_data_with_options(&options);
+        let (_, content) = data.iter().find(|(name, _)| name == "v1_api.ts").unwrap();
+        assert_eq!(
+            content,
+            "export interface V1Api {\n    f1(): number;\n    f2(): string;\n    // set f1\n    setF1(f1: number): void;\n    setF2(f2: string): void;\n}\n"
+        );
+    }
+
+    pub struct V1Store;
+
+    #[ts_interface(file_name = "v1_store.ts", ident = "V1Store")]
+    impl V1Store {
+        pub fn save(&self, v1: V1) -> std::sync::Arc<V1> {
+            std::sync::Arc::new(v1)
+        }
+    }
+
+    #[test]
+    fn test_pointer_return_type() {
+        let mut manager = DescriptorManager::default();
+        manager.add_api_descriptor(V1Store::__get_api_descriptor());
+        let data = manager.gen_data();
+        let (_, content) = data.iter().find(|(name, _)| name == "v1_store.ts").unwrap();
+        assert_eq!(
+            content,
+            "import { V1 } from './a'\n\nexport interface V1Store {\n    save(v1: V1): V1\n}\n"
+        );
+    }
+}
+
+// The generated code still refers to the gents crate, not this module.
+#[cfg(test)]
+mod test_local_gents_module {
+    use ::gents::DescriptorManager;
+    use gents_derives::{TS, ts_interface};
+
+    mod gents {}
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "point.ts")]
+    pub struct Point {
+        pub x: i32,
+    }
+
+    #[ts_interface(file_name = "point_api.ts", ident = "PointApi", export)]
+    impl Point {
+        pub fn moved(&self, dx: i32) -> Point {
+            Point { x: self.x + dx }
+        }
+    }
+
+    #[test]
+    fn test_local_gents_module() {
+        let mut manager = DescriptorManager::default();
+        manager.add_api_descriptor(<Point as ::gents::_TsAPI>::__get_api_descriptor());
+        let data = manager.gen_data();
+        let (_, content) = data
+            .iter()
+            .find(|(name, _)| name == "point_api.ts")
+            .unwrap();
+        assert_eq!(
+            content,
+            "import { Point } from './point'\n\nexport interface PointApi {\n    moved(dx: number): Point\n}\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_file_group {
+    use gents::*;
+    use gents_derives::TS;
+    use std::fs;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "a.ts", rename_all = "camelCase")]
+    pub struct V1 {
+        pub f1: u8,
+    }
+
+    #[test]
+    fn test_prune_and_unchanged_files() {
+        let dir = std::env::temp_dir().join("gents_test_prune");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let stale = dir.join("sub/stale.ts");
+        fs::write(&stale, "// DO NOT EDIT. CODE GENERATED BY gents.\n").unwrap();
+        let manual = dir.join("manual.ts");
+        fs::write(&manual, "export const a = 1\n").unwrap();
+        // The files of other backends share the header.
+        let kotlin = dir.join("sub/Stale.kt");
+        fs::write(&kotlin, "// DO NOT EDIT. CODE GENERATED BY gents.\n").unwrap();
+        // The links may point to the files of other groups.
+        let other = std::env::temp_dir().join("gents_test_prune_other.ts");
+        fs::write(&other, "// DO NOT EDIT. CODE GENERATED BY gents.\n").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&other, dir.join("link.ts")).unwrap();
+
+        let dir_str = dir.to_str().unwrap();
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.set_prune(true);
+        group.gen_files(dir_str, true);
+        assert!(!stale.exists());
+        assert!(manual.exists());
+        assert!(kotlin.exists());
+        #[cfg(unix)]
+        assert!(fs::symlink_metadata(dir.join("link.ts")).is_ok());
+        assert!(dir.join("a.ts").exists());
+        assert!(dir.join("index.ts").exists());
+
+        let modified = fs::metadata(dir.join("a.ts")).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.gen_files(dir_str, false);
+        let modified_again = fs::metadata(dir.join("a.ts")).unwrap().modified().unwrap();
+        assert_eq!(modified, modified_again);
+        // index.ts is not pruned without the prune option
+        assert!(dir.join("index.ts").exists());
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_file(&other);
+    }
+
+    // The JSON schemas have no header, so they are found by the manifest.
+    #[test]
+    fn test_prune_by_manifest() {
+        let dir = std::env::temp_dir().join("gents_test_prune_manifest");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let manual = dir.join("manual.json");
+        fs::write(&manual, "{}\n").unwrap();
+
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.add::<V2>();
+        group.set_prune(true);
+        group.gen_json_schemas(&dir);
+        assert!(dir.join("V2.schema.json").exists());
+
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.set_prune(true);
+        group.gen_json_schemas(&dir);
+        assert!(dir.join("V1.schema.json").exists());
+        assert!(!dir.join("V2.schema.json").exists());
+
+        // The Typescript files are not touched by the JSON schemas.
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.set_prune(true);
+        group.gen_files(&dir, false);
+        assert!(dir.join("V1.schema.json").exists());
+
+        // Nothing is generated this time, so all the schemas are stale.
+        let mut group = FileGroup::new();
+        group.set_prune(true);
+        assert!(group.gen_json_schemas(&dir).is_empty());
+        assert!(!dir.join("V1.schema.json").exists());
+        assert!(dir.join("a.ts").exists());
+        assert!(manual.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join(".gents-manifest")).unwrap(),
+            "a.ts\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "b.ts", rename_all = "camelCase", builder)]
+    pub struct V2 {
+        pub f1: u8,
+    }
+
+    #[test]
+    fn test_index_with_type_imports() {
+        let dir = std::env::temp_dir().join("gents_test_index_type_imports");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.add::<V2>();
+        group.set_format_options(FormatOptions {
+            type_imports: true,
+            import_extension: ImportExtension::Js,
+            ..FormatOptions::default()
+        });
+        group.gen_files(dir.to_str().unwrap(), true);
+        let index = fs::read_to_string(dir.join("index.ts")).unwrap();
+        assert_eq!(
+            index,
+            "// DO NOT EDIT. CODE GENERATED BY gents.\nexport type * from './a.js'\nexport * from './b.js'\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "c.ts", rename_all = "camelCase")]
+    pub struct V3 {
+        pub f1: V1,
+        pub f2: Vec<V2>,
+    }
+
+    #[test]
+    fn test_gen_bundle() {
+        let dir = std::env::temp_dir().join("gents_test_bundle");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("types.d.ts");
+        let mut group = FileGroup::new();
+        group.add::<V3>();
+        group.set_bundle_namespace("Api");
+        group.gen_bundle(&path);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+declare namespace Api {
+    export interface V1 {
+        f1: number
+    }
+
+    export interface V2 {
+        f1: number
+    }
+
+    export interface V3 {
+        f1: V1
+        f2: readonly V2[]
+    }
+}
+"#
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_declaration_files() {
+        let dir = std::env::temp_dir().join("gents_test_declaration_files");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<V3>();
+        group.set_declaration_files(true);
+        group.gen_files(dir.to_str().unwrap(), true);
+        assert!(dir.join("a.d.ts").exists());
+        assert!(dir.join("b.ts").exists());
+        assert!(dir.join("c.d.ts").exists());
+        assert!(dir.join("index.ts").exists());
+        assert!(!dir.join("a.ts").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", builder)]
+    pub struct User {
+        pub id: u32,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/auth/token.ts")]
+    pub struct Token {
+        pub value: String,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/secret.ts", internal)]
+    pub struct Secret {
+        pub value: String,
+    }
+
+    #[test]
+    fn test_index_per_directory() {
+        let dir = std::env::temp_dir().join("gents_test_index_per_directory");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<V1>();
+        group.add::<User>();
+        group.add::<Token>();
+        group.add::<Secret>();
+        group.set_index_options(IndexOptions {
+            per_directory: true,
+            named_exports: true,
+        });
+        group.set_format_options(FormatOptions {
+            type_imports: true,
+            ..FormatOptions::default()
+        });
+        group.gen_files(dir.to_str().unwrap(), true);
+        let read = |p: &str| fs::read_to_string(dir.join(p)).unwrap();
+        assert_eq!(
+            read("index.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+export type { V1 } from './a'
+export { type Token, type User, UserBuilder } from './models/index'
+"#
+        );
+        assert_eq!(
+            read("models/index.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+export type { Token } from './auth/index'
+export { type User, UserBuilder } from './user'
+"#
+        );
+        assert_eq!(
+            read("models/auth/index.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+export type { Token } from './token'
+"#
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    mod dup1 {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "dup1.ts")]
+        pub struct Dup {
+            pub f1: u8,
+        }
+    }
+
+    mod dup2 {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "dup2.ts")]
+        pub struct Dup {
+            pub f1: u8,
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "duplicated exported names in index.ts: `Dup` from './dup1' and './dup2'"
+    )]
+    fn test_index_duplicated_names() {
+        let dir = std::env::temp_dir().join("gents_test_index_duplicated_names");
+        let mut group = FileGroup::new();
+        group.add::<dup1::Dup>();
+        group.add::<dup2::Dup>();
+        group.gen_files(dir.to_str().unwrap(), true);
+    }
+}
+
+#[cfg(test)]
+mod test_name_collision {
+    use gents::*;
+    use gents_derives::TS;
+
+    mod api {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "api.ts")]
+        pub struct Error {
+            pub code: u16,
+        }
+    }
+
+    mod db {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "db/error.ts")]
+        pub struct Error {
+            pub message: String,
+        }
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "response.ts", builder)]
+    pub struct Response {
+        pub api: api::Error,
+        pub db: Option<db::Error>,
+    }
+
+    mod v1 {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "v1/status.ts")]
+        pub struct Status {
+            pub code: u16,
+        }
+    }
+
+    mod v2 {
+        #[derive(gents_derives::TS, Clone)]
+        #[ts(file_name = "v2/status.ts")]
+        pub struct Status {
+            pub message: String,
+        }
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "statuses.ts")]
+    pub struct Statuses {
+        pub v1: v1::Status,
+        pub v2: v2::Status,
+    }
+
+    fn gen_response(options: &FormatOptions) -> std::collections::HashMap<String, String> {
+        let mut manager = DescriptorManager::default();
+        Response::_register(&mut manager, true);
+        manager.gen_data_with_options(options).into_iter().collect()
+    }
+
+    #[test]
+    fn test_alias() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Alias,
+            ..FormatOptions::default()
+        };
+        let files = gen_response(&options);
+        assert_eq!(
+            files["response.ts"],
+            r#"import { Error as ApiError } from './api'
+import { Error as DbError } from './db/error'
+
+export interface Response {
+    api: ApiError
+    db?: DbError
+}
+
+export class ResponseBuilder {
+    private _api!: ApiError
+    private _db?: DbError
+    public api(value: ApiError) {
+        this._api = value
+        return this
+    }
+
+    public db(value: DbError) {
+        this._db = value
+        return this
+    }
+    public build() {
+        if (this._api === undefined) throw new Error('missing api')
+        return { api: this._api, db: this._db }
+    }
+}
+"#
+        );
+        assert!(files["api.ts"].starts_with("export interface Error {"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "type name conflicts in statuses.ts: `Status` is declared in v1/status.ts, v2/status.ts"
+    )]
+    fn test_error() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Error,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        Statuses::_register(&mut manager, true);
+        manager.gen_data_with_options(&options);
+    }
+
+    // The builders in the file would throw the declared `Error`.
+    #[test]
+    #[should_panic(
+        expected = "type name conflicts in api.ts: `Error` is a builtin type of Typescript"
+    )]
+    fn test_error_declaring_builtin_name() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Error,
+            ..FormatOptions::default()
+        };
+        gen_response(&options);
+    }
+
+    // The conflicting types are renamed in one file.
+    #[test]
+    fn test_alias_in_bundle() {
+        let mut group = FileGroup::new();
+        group.add::<Response>();
+        group.set_format_options(FormatOptions {
+            name_collision: NameCollision::Alias,
+            ..FormatOptions::default()
+        });
+        let path = std::env::temp_dir().join("gents_test_alias_in_bundle.ts");
+        group.gen_bundle(path.to_str().unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains(
+            r#"export interface ApiError {
+    code: number
+}
+
+export interface DbError {
+    message: string
+}
+
+export interface Response {
+    api: ApiError
+    db?: DbError
+}"#
+        ));
+        // The builtin `Error` is not shadowed.
+        assert!(content.contains("throw new Error('missing api')"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // The types sharing a name are fine unless a file uses both of them.
+    #[test]
+    fn test_error_in_different_files() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Error,
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        v1::Status::_register(&mut manager, true);
+        v2::Status::_register(&mut manager, true);
+        let files = manager.gen_data_with_options(&options);
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "type name conflicts: `Error` is a builtin type of Typescript")]
+    fn test_error_in_bundle() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Error,
+            ..FormatOptions::default()
+        };
+        let mut group = FileGroup::new();
+        group.add::<api::Error>();
+        group.add::<db::Error>();
+        group.set_format_options(options);
+        let path = std::env::temp_dir().join("gents_test_error_in_bundle.ts");
+        group.gen_bundle(path.to_str().unwrap());
+    }
+
+    #[test]
+    fn test_prefix_and_suffix() {
+        let options = FormatOptions {
+            name_collision: NameCollision::Error,
+            type_prefix: String::from("I"),
+            type_suffix: String::from("Dto"),
+            ..FormatOptions::default()
+        };
+        let mut manager = DescriptorManager::default();
+        api::Error::_register(&mut manager, true);
+        let data = manager.gen_data_with_options(&options);
+        assert_eq!(
+            data[0].1,
+            "export interface IErrorDto {\n    code: number\n}\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_file_naming {
+    use gents::*;
+    use gents_derives::TS;
+    use std::fs;
+
+    pub mod models {
+        pub mod user {
+            use gents_derives::TS;
+
+            #[derive(TS, Clone)]
+            pub struct UserProfile {
+                pub name: String,
+            }
+
+            #[derive(TS, Clone)]
+            pub struct User {
+                pub token: super::auth::Token,
+                pub profile: UserProfile,
+            }
+        }
+
+        pub mod auth {
+            use gents_derives::TS;
+
+            #[derive(TS, Clone)]
+            pub struct Token {
+                pub value: String,
+            }
+        }
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "session.ts")]
+    pub struct Session {
+        pub user: models::user::User,
+    }
+
+    #[test]
+    fn test_module_file_names() {
+        let mut manager = DescriptorManager::default();
+        Session::_register(&mut manager, true);
+        let data = manager.gen_data();
+        let names = data.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "tests/test_file_naming/models/auth.ts",
+                "tests/test_file_naming/models/user.ts",
+                "session.ts"
+            ]
+        );
+        assert_eq!(
+            data[1].1,
+            r#"import { Token } from './auth'
+
+export interface UserProfile {
+    name: string
+}
+
+export interface User {
+    token: Token
+    profile: UserProfile
+}
+"#
+        );
+        assert!(
+            data[2]
+                .1
+                .starts_with("import { User } from './tests/test_file_naming/models/user'\n")
+        );
+    }
+
+    #[test]
+    fn test_type_file_names() {
+        let dir = std::env::temp_dir().join("gents_test_type_file_names");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.set_file_naming(FileNaming::Type);
+        group.add::<Session>();
+        group.gen_files(dir.to_str().unwrap(), false);
+        let models = dir.join("tests/test_file_naming/models");
+        assert_eq!(
+            fs::read_to_string(models.join("user/user.ts")).unwrap(),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { Token } from '../auth/token'
+import { UserProfile } from './user_profile'
+
+export interface User {
+    token: Token
+    profile: UserProfile
+}
+"#
+        );
+        assert!(models.join("user/user_profile.ts").exists());
+        assert!(
+            fs::read_to_string(dir.join("session.ts"))
+                .unwrap()
+                .contains("from './tests/test_file_naming/models/user/user'")
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod test_export {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use std::fs;
+
+    pub mod models {
+        use gents_derives::TS;
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "user.ts", export)]
+        pub struct User {
+            pub name: String,
+        }
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "role.ts", export = "admin")]
+        pub struct Role {
+            pub level: u8,
+        }
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "not_exported.ts")]
+    pub struct NotExported {
+        pub f1: u8,
+    }
+
+    #[derive(Clone)]
+    pub struct Service;
+
+    #[ts_interface(file_name = "service.ts", export = "admin")]
+    impl Service {
+        pub fn role(&self, user: models::User) -> models::Role {
+            let _ = user;
+            models::Role { level: 0 }
+        }
+    }
+
+    fn gen_file_names(group: FileGroup, name: &str) -> Vec<String> {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        group.gen_files(dir.to_str().unwrap(), false);
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        files.sort();
+        let _ = fs::remove_dir_all(&dir);
+        files
+    }
+
+    #[test]
+    fn test_add_all() {
+        let mut group = FileGroup::new();
+        group.set_module_prefix("tests::tests::test_export");
+        group.add_all();
+        assert_eq!(
+            gen_file_names(group, "gents_test_add_all"),
+            vec!["role.ts", "service.ts", "user.ts"]
+        );
+    }
+
+    #[test]
+    fn test_add_by_tag() {
+        let mut group = FileGroup::new();
+        group.set_module_prefix("tests::tests::test_export");
+        group.add_by_tag("admin");
+        let mut group2 = FileGroup::new();
+        group2.set_module_prefix("tests::tests::test_export::models");
+        group2.add_by_tag("admin");
+        assert_eq!(
+            gen_file_names(group, "gents_test_add_by_tag"),
+            vec!["role.ts", "service.ts", "user.ts"]
+        );
+        assert_eq!(
+            gen_file_names(group2, "gents_test_add_by_tag_models"),
+            vec!["role.ts"]
+        );
+    }
+
+    // Generic types are added as the dependencies of other types instead.
+    #[test]
+    fn test_export_generic() {
+        let t = trybuild::TestCases::new();
+        t.compile_fail("ui/export_generic.rs");
+    }
+}
+
+#[cfg(test)]
+mod test_config {
+    use gents::*;
+    use std::any::TypeId;
+    use std::fs;
+
+    // Generated as `Dayjs`, which is not generated by gents.
+    #[derive(Clone, serde::Serialize, serde::Deserialize)]
+    pub struct Timestamp;
+
+    impl TS for Timestamp {
+        fn _register(manager: &mut DescriptorManager, _generic_base: bool) -> usize {
+            let descriptor = BuiltinTypeDescriptor {
+                ts_name: String::from("Dayjs"),
+            };
+            manager.registry(TypeId::of::<Self>(), Descriptor::BuiltinType(descriptor))
+        }
+
+        fn _ts_name() -> String {
+            String::from("Dayjs")
+        }
+    }
+
+    pub mod models {
+        use super::Timestamp;
+        use gents_derives::TS;
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "event.ts", export = "config")]
+        pub struct Event {
+            pub id: u64,
+            pub count: u32,
+            pub time: Timestamp,
+        }
+    }
+
+    const CONFIG: &str = r#"
+[format]
+indent = 2
+semicolons = true
+quote = "double"
+
+[integers]
+u64 = "bigint"
+
+[imports]
+Dayjs = "dayjs"
+
+[[groups]]
+name = "frontend"
+out_dir = "generated"
+index_file = true
+tags = ["config"]
+module_prefix = "tests::tests::test_config"
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let options = config.format_options();
+        assert_eq!(options.indent, Indent::Spaces(2));
+        assert!(options.semicolons);
+        assert_eq!(options.quote, QuoteStyle::Double);
+        assert_eq!(options.integer_types["u64"], "bigint");
+        assert_eq!(options.external_imports["Dayjs"], "dayjs");
+        let group = config.group("frontend");
+        assert_eq!(group.tags, vec!["config"]);
+        assert!(group.index_file);
+        assert!(!group.prune);
+        assert!(!group.zod_schemas);
+        assert!(!group.type_guards);
+        assert!(!group.api_clients);
+    }
+
+    #[test]
+    fn test_parse_enums() {
+        let config: Config = toml::from_str(
+            r#"
+rename_all = "camelCase"
+
+[format]
+indent = "tab"
+line_ending = "crlf"
+decl = "type"
+import_extension = "js"
+name_collision = "alias"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.rename_all, Some(Casing::CamelCase));
+        let options = config.format_options();
+        assert_eq!(options.indent, Indent::Tab);
+        assert_eq!(options.line_ending, LineEnding::CrLf);
+        assert_eq!(options.decl, DeclKind::TypeAlias);
+        assert_eq!(options.import_extension, ImportExtension::Js);
+        assert_eq!(options.name_collision, NameCollision::Alias);
+    }
+
+    // The unexpected values are reported when parsing.
+    #[test]
+    fn test_unexpected_value() {
+        let err = toml::from_str::<Config>("[format]\nquote = \"backtick\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `backtick`"));
+        let err = toml::from_str::<Config>("[integers]\nu64 = \"long\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `long`"));
+    }
+
+    // The misspelled keys are not ignored.
+    #[test]
+    fn test_unknown_field() {
+        let err = toml::from_str::<Config>("[format]\nsemicolon = true\n").unwrap_err();
+        assert!(err.to_string().contains("unknown field `semicolon`"));
+        let err = toml::from_str::<Config>("rename = \"camelCase\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown field `rename`"));
+        let err =
+            toml::from_str::<Config>("[[groups]]\nname = \"a\"\nout_dir = \"a\"\nindex = true\n")
+                .unwrap_err();
+        assert!(err.to_string().contains("unknown field `index`"));
+        // The generated code is chosen by the groups, not the format.
+        let err = toml::from_str::<Config>("[format]\nzod_schemas = true\n").unwrap_err();
+        assert!(err.to_string().contains("unknown field `zod_schemas`"));
+    }
+
+    #[test]
+    #[should_panic(expected = "group `backend` not found")]
+    fn test_group_not_found() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        config.group("backend");
+    }
+
+    #[test]
+    fn test_from_config() {
+        let root = std::env::temp_dir().join("gents_test_from_config");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("gents.toml"), CONFIG).unwrap();
+        let config = Config::from_file(&root.join("gents.toml"));
+        FileGroup::from_config(&config, "frontend").generate();
+
+        let read = |f: &str| fs::read_to_string(root.join("generated").join(f)).unwrap();
+        assert_eq!(
+            read("event.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { Dayjs } from "dayjs";
+
+export interface Event {
+  id: bigint;
+  count: number;
+  time: Dayjs;
+}
+"#
+        );
+        assert!(read("index.ts").contains("export * from \"./event\";"));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
+
+#[cfg(test)]
+mod test_json_schema {
+    use gents::*;
+    use gents_derives::TS;
+    use serde_json::{Value, json};
+    use std::fs;
+
+    /// A user.
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", rename_all = "camelCase")]
+    pub struct User {
+        /// The display name.
+        pub user_name: String,
+        pub age: u8,
+        pub role: Option<Role>,
+        pub scores: std::collections::HashMap<String, f64>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type", rename_all = "camelCase")]
+    pub enum Role {
+        Admin,
+        /// A guest with the level.
+        Guest(u32),
+    }
+
+    #[test]
+    fn test_gen_json_schemas() {
+        let dir = std::env::temp_dir().join("gents_test_json_schemas");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<User>();
+        let files = group.gen_json_schemas(dir.to_str().unwrap());
+        assert_eq!(
+            files,
+            vec![
+                dir.join("Role.schema.json"),
+                dir.join("models/User.schema.json")
+            ]
+        );
+        let read = |f: &str| -> Value {
+            serde_json::from_str(&fs::read_to_string(dir.join(f)).unwrap()).unwrap()
+        };
+        let user = read("models/User.schema.json");
+        assert_eq!(
+            user["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        assert_eq!(user["description"], "A user.");
+        assert_eq!(
+            user["properties"],
+            json!({
+                "userName": { "description": "The display name.", "type": "string" },
+                "age": { "type": "integer", "minimum": 0 },
+                "role": { "$ref": "../Role.schema.json" },
+                "scores": { "type": "object", "additionalProperties": { "type": "number" } },
+            })
+        );
+        assert_eq!(user["required"], json!(["userName", "age", "scores"]));
+        assert_eq!(
+            read("Role.schema.json")["oneOf"],
+            json!([
+                { "const": "admin" },
+                {
+                    "description": "A guest with the level.",
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "guest" },
+                        "value": { "type": "integer", "minimum": 0 },
+                    },
+                    "required": ["type", "value"],
+                },
+            ])
+        );
+        // The keys are sorted whatever the features of serde_json are.
+        let role = fs::read_to_string(dir.join("Role.schema.json")).unwrap();
+        assert!(role.starts_with(
+            r#"{
+  "$comment": "DO NOT EDIT. CODE GENERATED BY gents.",
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "oneOf": ["#
+        ));
+        assert!(role.ends_with("  \"title\": \"Role\"\n}\n"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_gen_json_schema_bundle() {
+        let path = std::env::temp_dir().join("gents_test_schema_bundle.json");
+        let mut group = FileGroup::new();
+        group.add::<User>();
+        group.gen_json_schema_bundle(path.to_str().unwrap());
+        let bundle: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let defs = bundle["$defs"].as_object().unwrap();
+        assert_eq!(defs.keys().collect::<Vec<_>>(), vec!["Role", "User"]);
+        assert_eq!(
+            defs["User"]["properties"]["role"],
+            json!({ "$ref": "#/$defs/Role" })
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    mod user_status {
+        use gents_derives::TS;
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "models/user.ts", rename = "Status")]
+        pub enum UserStatus {
+            Active,
+            Banned,
+        }
+    }
+
+    mod order_status {
+        use gents_derives::TS;
+
+        #[derive(TS, Clone)]
+        #[ts(file_name = "models/order.ts", rename = "Status")]
+        pub enum OrderStatus {
+            Paid,
+            Shipped,
+        }
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "account.ts", rename_all = "camelCase")]
+    pub struct Account {
+        pub user: user_status::UserStatus,
+        pub order: order_status::OrderStatus,
+    }
+
+    #[test]
+    fn test_gen_json_schemas_conflicting_names() {
+        let dir = std::env::temp_dir().join("gents_test_json_schemas_conflicts");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<Account>();
+        let mut files = group.gen_json_schemas(dir.to_str().unwrap());
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                dir.join("Account.schema.json"),
+                dir.join("models/OrderStatus.schema.json"),
+                dir.join("models/UserStatus.schema.json"),
+            ]
+        );
+        let read = |f: &str| -> Value {
+            serde_json::from_str(&fs::read_to_string(dir.join(f)).unwrap()).unwrap()
+        };
+        assert_eq!(
+            read("Account.schema.json")["properties"],
+            json!({
+                "user": { "$ref": "./models/UserStatus.schema.json" },
+                "order": { "$ref": "./models/OrderStatus.schema.json" },
+            })
+        );
+        assert_eq!(
+            read("models/UserStatus.schema.json")["oneOf"],
+            json!([{ "const": "active" }, { "const": "banned" }])
+        );
+        assert_eq!(
+            read("models/OrderStatus.schema.json")["oneOf"],
+            json!([{ "const": "paid" }, { "const": "shipped" }])
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod test_zod {
+    use gents::*;
+    use gents_derives::TS;
+    use std::collections::HashMap;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type", rename_all = "camelCase")]
+    pub enum Role {
+        Admin,
+        Guest(u32),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", rename_all = "camelCase")]
+    pub enum Level {
+        Low,
+        High,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "event.ts", tag = "kind", rename_all = "camelCase")]
+    pub enum Event {
+        Login(String),
+        Logout(u64),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+        pub role: Option<Role>,
+        pub levels: Vec<Level>,
+        pub avatar: Vec<u8>,
+        pub friends: Vec<Friend>,
+        pub scores: HashMap<String, u32>,
+        pub ranks: HashMap<u32, Level>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts")]
+    pub struct Friend {
+        pub name: String,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "page.ts")]
+    pub struct Page<T: TS + Clone + 'static> {
+        pub items: Vec<T>,
+        pub total: u64,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "response.ts")]
+    pub struct Response {
+        pub users: Page<User>,
+        pub events: Vec<Event>,
+    }
+
+    fn gen_response(options: FormatOptions) -> HashMap<String, String> {
+        let mut manager = DescriptorManager::default();
+        Response::_register(&mut manager, true);
+        let backend = TypescriptBackend {
+            options,
+            zod_schemas: true,
+            ..TypescriptBackend::default()
+        };
+        backend
+            .gen_files(&manager)
+            .into_iter()
+            .map(|(f, c)| {
+                (
+                    f,
+                    c.replacen("// DO NOT EDIT. CODE GENERATED BY gents.\n", "", 1),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zod_schemas() {
+        let files = gen_response(FormatOptions::default());
+        assert_eq!(
+            files["response.ts"],
+            r#"import { Event, EventSchema } from './event'
+import { Page, PageSchema } from './page'
+import { User, UserSchema } from './user'
+import { z } from 'zod'
+
+export interface Response {
+    users: Page<User>
+    events: readonly Event[]
+}
+
+export const ResponseSchema = z.object({
+    users: PageSchema(UserSchema),
+    events: z.array(EventSchema)
+})
+export type ResponseInput = z.infer<typeof ResponseSchema>
+"#
+        );
+        assert_eq!(
+            files["page.ts"],
+            r#"import { z } from 'zod'
+
+export interface Page<T> {
+    items: readonly T[]
+    total: number
+}
+
+export const PageSchema = <T extends z.ZodTypeAny>(TSchema: T) => z.object({
+    items: z.array(TSchema),
+    total: z.number()
+})
+"#
+        );
+        assert!(files["user.ts"].contains(
+            r#"export const UserSchema = z.object({
+    userName: z.string(),
+    role: RoleSchema.optional(),
+    levels: z.array(LevelSchema),
+    avatar: z.instanceof(Uint8Array),
+    friends: z.array(z.lazy(() => FriendSchema)),
+    scores: z.record(z.string(), z.number()),
+    ranks: z.record(z.coerce.number(), LevelSchema)
+})
+export type UserInput = z.infer<typeof UserSchema>"#
+        ));
+        assert!(files["role.ts"].contains(
+            r#"export const RoleSchema = z.union([
+    z.literal('admin'),
+    z.object({ type: z.literal('guest'), value: z.number() })
+])"#
+        ));
+        assert!(files["role.ts"].contains(
+            r#"export const LevelSchema = z.enum([
+    'low',
+    'high'
+])
+export type LevelInput = z.infer<typeof LevelSchema>"#
+        ));
+        assert!(files["event.ts"].contains(
+            r#"export const EventSchema = z.discriminatedUnion('kind', [
+    z.object({ kind: z.literal('login'), value: z.string() }),
+    z.object({ kind: z.literal('logout'), value: z.number() })
+])
+export type EventInput = z.infer<typeof EventSchema>"#
+        ));
+    }
+
+    #[test]
+    fn test_zod_with_type_imports() {
+        let mut integer_types = std::collections::BTreeMap::new();
+        integer_types.insert(String::from("u64"), String::from("bigint"));
+        let options = FormatOptions {
+            type_imports: true,
+            trailing_comma: true,
+            integer_types,
+            ..FormatOptions::default()
+        };
+        let files = gen_response(options);
+        assert!(files["response.ts"].starts_with(
+            r#"import { type Event, EventSchema } from './event'
+import { type Page, PageSchema } from './page'
+import { type User, UserSchema } from './user'
+import { z } from 'zod'
+"#
+        ));
+        assert!(files["page.ts"].contains("    total: z.bigint(),\n})"));
+    }
+}
+
+#[cfg(test)]
+mod test_type_guards {
+    use gents::*;
+    use gents_derives::TS;
+    use std::collections::HashMap;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type", rename_all = "camelCase")]
+    pub enum Role {
+        Admin,
+        Guest(u32),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "page.ts")]
+    pub struct Page<T: TS + Clone + 'static> {
+        pub items: Vec<T>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+        pub role: Option<Role>,
+        pub avatar: Vec<u8>,
+        pub point: (f64, f64),
+        pub tags: HashMap<String, bool>,
+        pub scores: HashMap<u32, f64>,
+        pub roles: Page<Role>,
+        pub pages: Page<Vec<u8>>,
+    }
+
+    #[test]
+    fn test_type_guards() {
+        let mut manager = DescriptorManager::default();
+        User::_register(&mut manager, true);
+        let backend = TypescriptBackend {
+            type_guards: true,
+            ..TypescriptBackend::default()
+        };
+        let files = backend
+            .gen_files(&manager)
+            .into_iter()
+            .map(|(f, c)| {
+                (
+                    f,
+                    c.replacen("// DO NOT EDIT. CODE GENERATED BY gents.\n", "", 1),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            files["role.ts"],
+            r#"export type Role =
+    | 'admin'
+    | { type: 'guest'; value: number }
+
+export function isRole(x: unknown): x is Role {
+    if (x === 'admin') return true
+    if (typeof x !== 'object' || x === null) return false
+    const o = x as Record<string, unknown>
+    if (o.type === 'guest') return typeof o.value === 'number'
+    return false
+}
+"#
+        );
+        assert!(files["page.ts"].contains(
+            "export function isPage<T>(x: unknown, isT: (x: unknown) => x is T): x is Page<T> {"
+        ));
+        assert!(files["user.ts"].starts_with(
+            r#"import { Page, isPage } from './page'
+import { Role, isRole } from './role'
+"#
+        ));
+        assert!(files["user.ts"].contains(
+            r#"export function isUser(x: unknown): x is User {
+    if (typeof x !== 'object' || x === null) return false
+    const o = x as Record<string, unknown>
+    if (!(typeof o.userName === 'string')) return false
+    if (!(o.role === undefined || isRole(o.role))) return false
+    if (!(o.avatar instanceof Uint8Array)) return false
+    if (!(Array.isArray(o.point) && o.point.length === 2 && typeof o.point[0] === 'number' && typeof o.point[1] === 'number')) return false
+    if (!(o.tags instanceof Map && Array.from(o.tags).every(([k, v]) => typeof k === 'string' && typeof v === 'boolean'))) return false
+    if (!(o.scores instanceof Map && Array.from(o.scores).every(([k, v]) => typeof k === 'number' && typeof v === 'number'))) return false
+    if (!isPage(o.roles, isRole)) return false
+    if (!isPage(o.pages, (v: unknown): v is Uint8Array => v instanceof Uint8Array)) return false
+    return true
+}"#
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_backend {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use std::fs;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/page.ts")]
+    pub struct Page<T: TS + Clone + 'static> {
+        pub items: Vec<T>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts")]
+    pub struct User {
+        pub name: String,
+        pub role: Role,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type")]
+    pub enum Role {
+        Admin,
+        Guest(u32),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "response.ts")]
+    pub struct Response {
+        pub users: Page<User>,
+    }
+
+    #[derive(Clone)]
+    pub struct Directory;
+
+    #[ts_interface(file_name = "directory.ts", ident = "DirectoryApi")]
+    impl Directory {
+        pub fn users(&self, _page: u32) -> Page<User> {
+            Page { items: vec![] }
+        }
+    }
+
+    // Lists the types of every file with their fields and the types they use,
+    // and the types used by the APIs.
+    struct Outline;
+
+    impl Backend for Outline {
+        fn gen_files(&self, manager: &DescriptorManager) -> Vec<(String, String)> {
+            let lines = manager
+                .declared_types()
+                .into_iter()
+                .map(|idx| {
+                    let (file_name, name) = manager.type_key(idx);
+                    let params = manager.generic_params(idx).join(", ");
+                    let fields = manager
+                        .fields(idx)
+                        .iter()
+                        .map(|f| f.ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let tag = manager.tag(idx).unwrap_or("-");
+                    let used = manager
+                        .dependencies(idx)
+                        .into_iter()
+                        .map(|dep| manager.type_key(dep).1)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!(
+                        "{} {}<{}> {} [{}]: {}\n",
+                        file_name, name, params, tag, fields, used
+                    )
+                })
+                .collect::<String>();
+            let apis = manager
+                .apis()
+                .iter()
+                .map(|api| {
+                    let used = manager
+                        .api_dependencies(api)
+                        .into_iter()
+                        .map(|dep| manager.type_key(dep).1)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("{} {}: {}\n", api.file_name, api.name, used)
+                })
+                .collect::<String>();
+            vec![(String::from("outline.txt"), lines + &apis)]
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let dir = std::env::temp_dir().join("gents_test_custom_backend");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<Response>();
+        group.add_api::<Directory>();
+        let files = group.gen_with(&Outline, dir.to_str().unwrap());
+        assert_eq!(files, vec![dir.join("outline.txt")]);
+        let content = fs::read_to_string(dir.join("outline.txt")).unwrap();
+        let mut lines = content.lines().collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "directory.ts DirectoryApi: Page, User",
+                "models/page.ts Page<T> - [items]: ",
+                "models/user.ts User<> - [name, role]: Role",
+                "response.ts Response<> - [users]: Page, User",
+                "role.ts Role<> type [Admin, Guest]: ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_backend() {
+        let dir = std::env::temp_dir().join("gents_test_typescript_backend");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<Role>();
+        let backend = TypescriptBackend {
+            index_file: true,
+            ..TypescriptBackend::default()
+        };
+        let files = group.gen_with(&backend, dir.to_str().unwrap());
+        assert_eq!(files, vec![dir.join("index.ts"), dir.join("role.ts")]);
+        let content = fs::read_to_string(dir.join("index.ts")).unwrap();
+        assert!(content.starts_with("// DO NOT EDIT. CODE GENERATED BY gents."));
+    }
+}
+
+#[cfg(test)]
+mod test_python {
+    use gents::*;
+    use gents_derives::TS;
+    use std::fs;
+
+    /// A user.
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", rename_all = "camelCase")]
+    pub struct User {
+        /// The display name.
+        pub user_name: String,
+        pub age: Option<u8>,
+        pub role: Role,
+        pub tags: Vec<String>,
+        pub avatar: Vec<u8>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/page.ts")]
+    pub struct Page<T: TS + Clone + 'static> {
+        pub items: Vec<T>,
+        pub total: u64,
+    }
+
+    /// The role of a user.
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type", rename_all = "camelCase")]
+    pub enum Role {
+        Admin,
+        /// A guest with the level.
+        Guest(u32),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/users.ts")]
+    pub struct Users {
+        pub page: Page<User>,
+        pub scores: std::collections::HashMap<String, (f64, bool)>,
+    }
+
+    #[test]
+    fn test_gen_python_files() {
+        let dir = std::env::temp_dir().join("gents_test_python");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<Users>();
+        let files = group.gen_python_files(dir.to_str().unwrap());
+        assert_eq!(
+            files,
+            vec![
+                dir.join("__init__.py"),
+                dir.join("models/__init__.py"),
+                dir.join("models/page.py"),
+                dir.join("models/user.py"),
+                dir.join("models/users.py"),
+                dir.join("role.py"),
+            ]
+        );
+        let read = |f: &str| fs::read_to_string(dir.join(f)).unwrap();
+        assert_eq!(
+            read("models/user.py"),
+            r#"# DO NOT EDIT. CODE GENERATED BY gents.
+from __future__ import annotations
+
+from typing import NotRequired, TypedDict
+
+from ..role import Role
+
+
+class User(TypedDict):
+    """A user."""
+
+    # The display name.
+    userName: str
+    age: NotRequired[int]
+    role: Role
+    tags: list[str]
+    avatar: bytes
+"#
+        );
+        assert_eq!(
+            read("models/page.py"),
+            r#"# DO NOT EDIT. CODE GENERATED BY gents.
+from __future__ import annotations
+
+from typing import Generic, TypeVar, TypedDict
+
+T = TypeVar('T')
+
+
+class Page(TypedDict, Generic[T]):
+    items: list[T]
+    total: int
+"#
+        );
+        assert_eq!(
+            read("models/users.py"),
+            r#"# DO NOT EDIT. CODE GENERATED BY gents.
+from __future__ import annotations
+
+from typing import TypedDict
+
+from .page import Page
+from .user import User
+
+
+class Users(TypedDict):
+    page: Page[User]
+    scores: dict[str, tuple[float, bool]]
+"#
+        );
+        assert_eq!(
+            read("role.py"),
+            r#"# DO NOT EDIT. CODE GENERATED BY gents.
+from __future__ import annotations
+
+from typing import Literal, TypedDict, Union
+
+
+class RoleGuest(TypedDict):
+    """A guest with the level."""
+
+    type: Literal['guest']
+    value: int
+
+
+# The role of a user.
+Role = Union[Literal['admin'], RoleGuest]
+"#
+        );
+        assert_eq!(
+            read("__init__.py"),
+            "# DO NOT EDIT. CODE GENERATED BY gents.\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_mobile {
+    use gents::*;
+    use gents_derives::TS;
+    use std::fs;
+
+    /// A user.
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts")]
+    pub struct User {
+        /// The display name.
+        pub user_name: String,
+        pub age: Option<u8>,
+        pub role: Role,
+        pub status: Status,
+        pub tags: Vec<String>,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "role.ts", tag = "type", rename_all = "camelCase")]
+    pub enum Role {
+        Admin,
+        /// A guest with the level.
+        Guest(u32),
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", rename_all = "camelCase")]
+    pub enum Status {
+        Active,
+        Banned,
+    }
+
+    fn generate<B: Backend>(backend: &B, name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<User>();
+        group.gen_with(backend, dir.to_str().unwrap());
+        dir
+    }
+
+    #[test]
+    fn test_kotlin() {
+        let backend = KotlinBackend {
+            package: String::from("com.example"),
+            ..KotlinBackend::default()
+        };
+        let dir = generate(&backend, "gents_test_kotlin");
+        assert_eq!(
+            fs::read_to_string(dir.join("models/User.kt")).unwrap(),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+package com.example.models
+
+import com.example.Role
+import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+
+@Serializable
+enum class Status {
+    @SerialName("active")
+    Active,
+    @SerialName("banned")
+    Banned,
+}
+
+/** A user. */
+@Serializable
+data class User(
+    /** The display name. */
+    @SerialName("user_name")
+    val userName: String,
+    val age: UByte? = null,
+    val role: Role,
+    val status: Status,
+    val tags: List<String>,
+)
+"#
+        );
+        let role = fs::read_to_string(dir.join("Role.kt")).unwrap();
+        assert!(role.contains("package com.example\n"));
+        assert!(role.contains(
+            r#"@Serializable(with = RoleSerializer::class)
+sealed class Role {
+    object Admin : Role()
+    /** A guest with the level. */
+    data class Guest(val value: UInt) : Role()
+}
+"#
+        ));
+        assert!(role.contains(r#"is Role.Admin -> JsonPrimitive("admin")"#));
+        assert!(role.contains(r#"put("type", JsonPrimitive("guest"))"#));
+        assert!(role.contains(
+            r#""guest" -> Role.Guest(json.decodeFromJsonElement(element.jsonObject.getValue("value")))"#
+        ));
+    }
+
+    #[test]
+    fn test_swift() {
+        let dir = generate(&SwiftBackend::default(), "gents_test_swift");
+        assert_eq!(
+            fs::read_to_string(dir.join("models/User.swift")).unwrap(),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import Foundation
+
+public enum Status: String, Codable {
+    case active = "active"
+    case banned = "banned"
+}
+
+/// A user.
+public struct User: Codable {
+    /// The display name.
+    public var userName: String
+    public var age: UInt8?
+    public var role: Role
+    public var status: Status
+    public var tags: [String]
+
+    enum CodingKeys: String, CodingKey {
+        case userName = "user_name"
+        case age
+        case role
+        case status
+        case tags
+    }
+}
+"#
+        );
+        let role = fs::read_to_string(dir.join("Role.swift")).unwrap();
+        assert!(role.contains(
+            r#"public enum Role: Codable {
+    case admin
+    /// A guest with the level.
+    case guest(UInt32)
+
+    private enum CodingKeys: String, CodingKey {
+        case tag = "type"
+        case value
+    }
+"#
+        ));
+        assert!(role.contains(r#"case "admin": self = .admin"#));
+        assert!(role.contains(
+            r#"case "guest": self = .guest(try container.decode(UInt32.self, forKey: .value))"#
+        ));
+        assert!(role.contains(r#"try container.encode("guest", forKey: .tag)"#));
+        assert!(!dir.join("JSONValue.swift").exists());
+    }
+}
+
+#[cfg(test)]
+mod test_openapi {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use serde_json::{Value, json};
+    use std::fs;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+        pub age: Option<u8>,
+    }
+
+    pub struct UserService;
+
+    #[ts_interface(file_name = "user_api.ts", ident = "UserApi")]
+    impl UserService {
+        /// Get a user by the id.
+        #[ts(http = "GET /users/{user_id}")]
+        pub fn get_user(&self, user_id: u32, verbose: Option<bool>) -> User {
+            User {
+                user_name: format!("user{}", user_id),
+                age: verbose.map(|_| 20),
+            }
+        }
+
+        #[ts(http = "POST /users")]
+        pub fn create_user(&self, user: User) -> u32 {
+            let _ = user;
+            0
+        }
+
+        pub fn rename(&self, user_id: u32, name: String) {
+            let _ = (user_id, name);
+        }
+    }
+
+    #[test]
+    fn test_gen_openapi() {
+        let dir = std::env::temp_dir().join("gents_test_openapi");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<User>();
+        group.add_api::<UserService>();
+        let backend = OpenApiBackend {
+            title: String::from("Users"),
+            version: String::from("1.0.0"),
+            ..OpenApiBackend::default()
+        };
+        let files = group.gen_with(&backend, dir.to_str().unwrap());
+        assert_eq!(files, vec![dir.join("openapi.json")]);
+        let doc: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("openapi.json")).unwrap()).unwrap();
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["info"], json!({ "title": "Users", "version": "1.0.0" }));
+        assert_eq!(
+            doc["components"]["schemas"]["User"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "userName": { "type": "string" },
+                    "age": { "type": "integer", "minimum": 0 },
+                },
+                "required": ["userName"],
+            })
+        );
+        assert_eq!(
+            doc["paths"]["/users/{user_id}"]["get"],
+            json!({
+                "operationId": "userApiGetUser",
+                "tags": ["UserApi"],
+                "description": "Get a user by the id.",
+                "parameters": [
+                    {
+                        "name": "user_id",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "integer", "minimum": 0 },
+                    },
+                    {
+                        "name": "verbose",
+                        "in": "query",
+                        "required": false,
+                        "schema": { "type": "boolean" },
+                    },
+                ],
+                "responses": {
+                    "200": {
+                        "description": "OK",
+                        "content": {
+                            "application/json": {
+                                "schema": { "$ref": "#/components/schemas/User" },
+                            },
+                        },
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            doc["paths"]["/users"]["post"]["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+        // Methods without `http` are posted to `/{api}/{method}` with an
+        // object of their params.
+        let rename = &doc["paths"]["/UserApi/rename"]["post"];
+        assert_eq!(
+            rename["requestBody"]["content"]["application/json"]["schema"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "userId": { "type": "integer", "minimum": 0 },
+                    "name": { "type": "string" },
+                },
+                "required": ["userId", "name"],
+            })
+        );
+        assert_eq!(
+            rename["responses"],
+            json!({ "204": { "description": "No Content" } })
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_api_client {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use std::fs;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+    }
+
+    pub struct UserService;
+
+    #[ts_interface(file_name = "api/user_api.ts", ident = "UserApi", async = "true")]
+    impl UserService {
+        /// Get a user by the id.
+        #[ts(http = "GET /users/{user_id}")]
+        pub async fn get_user(&self, user_id: u32, verbose: bool) -> User {
+            let _ = verbose;
+            User {
+                user_name: format!("user{}", user_id),
+            }
+        }
+
+        pub async fn rename(&self, user_id: u32, name: String) {
+            let _ = (user_id, name);
+        }
+    }
+
+    #[test]
+    fn test_gen_api_client() {
+        let dir = std::env::temp_dir().join("gents_test_api_client");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add_api::<UserService>();
+        group.set_api_clients(true);
+        group.gen_files(dir.to_str().unwrap(), false);
+        let api = fs::read_to_string(dir.join("api/user_api.ts")).unwrap();
+        assert_eq!(
+            api,
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { User } from '../models/user'
+import { ApiRequest, AsyncTransport } from '../transport'
+
+export interface UserApi {
+    // Get a user by the id.
+    async getUser(userId: number, verbose: boolean): Promise<User>
+    async rename(userId: number, name: string): Promise<void>
+}
+
+export class UserApiClient implements UserApi {
+    constructor(private readonly transport: AsyncTransport) {
+    }
+
+    // Get a user by the id.
+    async getUser(userId: number, verbose: boolean): Promise<User> {
+        const request: ApiRequest = {
+            method: 'UserApi.getUser',
+            params: [userId, verbose],
+            http: { method: 'GET', path: `/users/${encodeURIComponent(String(userId))}`, query: { verbose } }
+        }
+        return (await this.transport(request)) as User
+    }
+
+    async rename(userId: number, name: string): Promise<void> {
+        const request: ApiRequest = {
+            method: 'UserApi.rename',
+            params: [userId, name],
+            http: { method: 'POST', path: '/UserApi/rename', query: {}, body: { userId, name } }
+        }
+        await this.transport(request)
+    }
+}
+"#
+        );
+        let transport = fs::read_to_string(dir.join("transport.ts")).unwrap();
+        assert!(transport.contains("export interface ApiRequest {"));
+        assert!(
+            transport.contains(
+                "export type AsyncTransport = (request: ApiRequest) => Promise<unknown>\n"
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_dispatch {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use serde_json::json;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+    }
+
+    pub struct UserService {
+        users: Vec<User>,
+    }
+
+    #[ts_interface(file_name = "user_api.ts", ident = "UserApi", dispatch)]
+    impl UserService {
+        pub fn get_user(&self, idx: usize) -> Result<User, String> {
+            self.users
+                .get(idx)
+                .cloned()
+                .ok_or(format!("no user {}", idx))
+        }
+
+        pub fn add_user(&mut self, user: User, prefix: Option<String>) {
+            let user_name = format!("{}{}", prefix.unwrap_or_default(), user.user_name);
+            self.users.push(User { user_name });
+        }
+
+        pub fn count(&self, names: Vec<String>) -> usize {
+            names.len() + self.users.len()
+        }
+
+        pub fn find(&self, user_name: &str) -> Option<usize> {
+            self.users.iter().position(|u| u.user_name == user_name)
+        }
+    }
+
+    pub struct AsyncService;
+
+    #[ts_interface(file_name = "async_api.ts", async = "true", dispatch)]
+    impl AsyncService {
+        pub async fn double(&self, n: u32) -> u32 {
+            n * 2
+        }
+
+        // Not shadowed by the generated `gents_dispatch`.
+        pub async fn dispatch(&self, n: u32) -> u32 {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut service = UserService { users: vec![] };
+        let result = service.gents_dispatch("addUser", json!([{ "userName": "alice" }, "@"]));
+        assert_eq!(result.unwrap(), json!(null));
+        // The trailing `Option` can be left out.
+        service
+            .gents_dispatch("UserApi.addUser", json!([{ "userName": "bob" }]))
+            .unwrap();
+        assert_eq!(service.users.len(), 2);
+        assert_eq!(service.users[0].user_name, "@alice");
+        // The results are the JSON of the methods called directly.
+        let user = service.get_user(1).unwrap();
+        assert_eq!(user.user_name, "bob");
+        assert_eq!(
+            service.gents_dispatch("getUser", json!([1])).unwrap(),
+            serde_json::to_value(user).unwrap()
+        );
+        assert_eq!(service.count(vec![String::from("x")]), 3);
+        assert_eq!(
+            service.gents_dispatch("count", json!([["x"]])).unwrap(),
+            json!(3)
+        );
+        assert_eq!(service.find("@alice"), Some(0));
+        assert_eq!(
+            service.gents_dispatch("find", json!(["@alice"])).unwrap(),
+            json!(0)
+        );
+        match service.gents_dispatch("getUser", json!([2])) {
+            Err(DispatchError::Err(e)) => assert_eq!(e, json!("no user 2")),
+            _ => panic!("expected the error of the method"),
+        }
+        assert!(matches!(
+            service.gents_dispatch("getUser", json!(["a"])),
+            Err(DispatchError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            service.gents_dispatch("getUser", json!([1, 2])),
+            Err(DispatchError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            service.gents_dispatch("get_user", json!([1])),
+            Err(DispatchError::UnknownMethod(_))
+        ));
+    }
+
+    #[test]
+    fn test_async_dispatch() {
+        let service = AsyncService;
+        let mut future = pin!(service.gents_dispatch("double", json!([21])));
+        let mut cx = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => assert_eq!(result.unwrap(), json!(42)),
+            Poll::Pending => panic!("the future should be ready"),
+        }
+        let mut future = pin!(service.double(21));
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(42));
+        let mut future = pin!(service.gents_dispatch("dispatch", json!([1])));
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => assert_eq!(result.unwrap(), json!(2)),
+            Poll::Pending => panic!("the future should be ready"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_wasm {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use std::fs;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase", builder)]
+    pub struct User {
+        /// Like "#admin", which does not close the raw string.
+        pub user_name: String,
+    }
+
+    pub struct UserService;
+
+    #[ts_interface(file_name = "user_api.ts", ident = "UserApi")]
+    impl UserService {
+        pub fn get_user(&self, user_name: String) -> User {
+            User { user_name }
+        }
+    }
+
+    #[test]
+    fn test_gen_wasm_section() {
+        let dir = std::env::temp_dir().join("gents_test_wasm");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.add::<User>();
+        group.add::<String>();
+        group.add_api::<UserService>();
+        let files = group.gen_with(&WasmBindgenBackend::default(), dir.to_str().unwrap());
+        assert_eq!(files, vec![dir.join("gents_ts.rs")]);
+        let content = fs::read_to_string(dir.join("gents_ts.rs")).unwrap();
+        let expected =
+            fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/ui/gents_ts.rs")).unwrap();
+        assert_eq!(content, expected);
+    }
+
+    // `ui/gents_ts.rs` is the output above, which compiles with the
+    // re-exports of gents only.
+    #[test]
+    fn test_compile_wasm_section() {
+        let t = trybuild::TestCases::new();
+        t.pass("ui/wasm_section.rs");
+    }
+}
+
+// Expand `#[ts_interface(wasm)]` with wasm-bindgen, which is compiled for the
+// host as well.
+#[cfg(test)]
+mod test_wasm_bindgen {
+    use gents::wasm_bindgen::prelude::wasm_bindgen;
+    use gents::*;
+    use gents_derives::ts_interface;
+    use std::collections::HashMap;
+
+    #[wasm_bindgen(wasm_bindgen = ::gents::wasm_bindgen)]
+    pub struct Scores {
+        scores: HashMap<String, u32>,
+    }
+
+    #[ts_interface(file_name = "scores.ts", ident = "ScoresApi", wasm)]
+    impl Scores {
+        pub fn get_score(&self, name: &str) -> Option<u32> {
+            self.scores.get(name).copied()
+        }
+
+        pub fn set_score(&mut self, name: String, score: u32) {
+            self.scores.insert(name, score);
+        }
+
+        pub fn all_scores(&self) -> HashMap<String, u32> {
+            self.scores.clone()
+        }
+
+        pub fn parse_score(score: String) -> Result<u32, String> {
+            score
+                .parse()
+                .map_err(|_| format!("invalid score {}", score))
+        }
+
+        pub async fn version() -> String {
+            String::from("1.0.0")
+        }
+    }
+
+    #[test]
+    fn test_wasm_methods() {
+        let api = <Scores as _TsAPI>::__get_api_descriptor();
+        let methods = api
+            .methods
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            vec!["getScore", "setScore", "allScores", "parseScore", "version"]
+        );
+        let mut scores = Scores {
+            scores: HashMap::new(),
+        };
+        scores.set_score(String::from("alice"), 3);
+        assert_eq!(scores.get_score("alice"), Some(3));
+    }
+
+    // wasm-bindgen requires `'static` futures, which can not borrow `self`.
+    #[test]
+    fn test_wasm_async_self() {
+        let t = trybuild::TestCases::new();
+        t.compile_fail("ui/wasm_async_self.rs");
+    }
+}
+
+#[cfg(test)]
+mod test_tauri {
+    use gents::*;
+    use gents_derives::{TS, ts_command};
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    // Like `tauri::State`, which is injected by Tauri.
+    pub struct State<'a, T>(&'a T);
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "models/user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+    }
+
+    // The errors need not implement `TS`.
+    #[derive(Debug)]
+    pub struct NotFound(pub u32);
+
+    /// Get a user by the id.
+    #[ts_command(file_name = "commands.ts", export = "desktop")]
+    pub fn get_user(state: State<'_, Vec<User>>, user_id: u32) -> Result<User, NotFound> {
+        let State(users) = state;
+        users
+            .get(user_id as usize)
+            .cloned()
+            .ok_or(NotFound(user_id))
+    }
+
+    #[ts_command(file_name = "commands.ts")]
+    pub fn save_user(user: User, display_name: &str) {
+        let _ = (user, display_name);
+    }
+
+    #[ts_command(file_name = "commands.ts")]
+    pub fn save(name: String) -> Result<(), String> {
+        let _ = name;
+        Ok(())
+    }
+
+    #[ts_command(file_name = "commands.ts")]
+    pub fn reset() {}
+
+    #[ts_command(file_name = "commands.ts")]
+    pub fn find_users(prefix: Option<String>, limit: u32, offset: Option<u32>) -> Vec<User> {
+        let _ = (prefix, limit, offset);
+        vec![]
+    }
+
+    #[ts_command(file_name = "app/app.ts")]
+    pub async fn version() -> String {
+        String::from("1.0.0")
+    }
+
+    #[test]
+    fn test_gen_commands() {
+        let dir = std::env::temp_dir().join("gents_test_tauri");
+        let _ = fs::remove_dir_all(&dir);
+        let mut group = FileGroup::new();
+        group.set_module_prefix("tests::tests::test_tauri");
+        group.add_all();
+        let mut external_imports = BTreeMap::new();
+        external_imports.insert(
+            String::from("invoke"),
+            String::from("@tauri-apps/api/tauri"),
+        );
+        group.set_format_options(FormatOptions {
+            external_imports,
+            ..FormatOptions::default()
+        });
+        group.gen_files(dir.to_str().unwrap(), false);
+        let commands = fs::read_to_string(dir.join("commands.ts")).unwrap();
+        assert_eq!(
+            commands,
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { User } from './models/user'
+import { invoke } from '@tauri-apps/api/tauri'
+
+export async function findUsers(prefix: string | undefined, limit: number, offset?: number): Promise<readonly User[]> {
+    return invoke<readonly User[]>('find_users', { prefix, limit, offset })
+}
+
+// Get a user by the id.
+export async function getUser(userId: number): Promise<User> {
+    return invoke<User>('get_user', { userId })
+}
+
+export async function reset(): Promise<void> {
+    return invoke<void>('reset')
+}
+
+export async function save(name: string): Promise<void> {
+    return invoke<void>('save', { name })
+}
+
+export async function saveUser(user: User, displayName: string): Promise<void> {
+    return invoke<void>('save_user', { user, displayName })
+}
+"#
+        );
+        let app = fs::read_to_string(dir.join("app/app.ts")).unwrap();
+        assert!(app.contains("return invoke<string>('version')\n"));
+        // The types of the commands are registered as well.
+        assert!(dir.join("models/user.ts").exists());
+    }
+}
+
+#[cfg(test)]
+mod test_api_registry {
+    use gents::*;
+    use gents_derives::{TS, ts_interface};
+    use std::collections::HashMap;
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "user.ts", rename_all = "camelCase")]
+    pub struct User {
+        pub user_name: String,
+    }
+
+    #[derive(TS, Clone)]
+    #[ts(file_name = "page.ts")]
+    pub struct Page<T> {
+        pub items: Vec<T>,
+    }
+
+    pub struct UserService;
+
+    #[ts_interface(file_name = "user_api.ts", ident = "UserApi")]
+    impl UserService {
+        pub fn find(&self, ids: Vec<u32>, limit: Option<u32>) -> Page<User> {
+            let limit = limit.map_or(ids.len(), |l| l as usize);
+            let items = ids
+                .iter()
+                .take(limit)
+                .map(|id| User {
+                    user_name: format!("user{}", id),
+                })
+                .collect();
+            Page { items }
+        }
+
+        pub fn save(&self, users: Vec<User>) -> Option<u32> {
+            let _ = users;
+            None
+        }
+    }
+
+    #[test]
+    fn test_add_api_registers_types() {
+        let mut manager = DescriptorManager::default();
+        manager.add_api_descriptor(<UserService as _TsAPI>::__get_api_descriptor());
+        let files = manager.gen_data().into_iter().collect::<HashMap<_, _>>();
+        let mut names = files.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["page.ts", "user.ts", "user_api.ts"]);
+        assert_eq!(
+            files["user_api.ts"],
+            r#"import { Page } from './page'
+import { User } from './user'
+
+export interface UserApi {
+    find(ids: readonly number[], limit: number): Page<User>
+    save(users: readonly User[]): number
+}
+"#
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_cli {
+    use std::fs;
+    use std::path::Path;
+
+    fn write(root: &Path, file: &str, content: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_gen_from_sources() {
+        let root = std::env::temp_dir().join("gents_test_cli");
+        let _ = fs::remove_dir_all(&root);
+        write(
+            &root,
+            "gents.toml",
+            r#"
+[[groups]]
+name = "all"
+out_dir = "generated"
+index_file = true
+"#,
+        );
+        write(
+            &root,
+            "src/lib.rs",
+            r#"
+mod models;
+use models::user::User;
+
+#[derive(TS, Clone)]
+#[ts(file_name = "session.ts", rename_all = "camelCase")]
+pub struct Session<T> {
+    pub session_id: String,
+    pub user: User,
+    pub data: Vec<T>,
+}
+"#,
+        );
+        write(&root, "src/models/mod.rs", "pub mod user;\npub mod auth;\n");
+        write(
+            &root,
+            "src/models/user.rs",
+            r#"
+use super::auth::Token;
+
+/// A user.
+#[derive(gents_derives::TS, Clone)]
+pub struct User {
+    pub name: String,
+    pub tokens: Vec<Token>,
+    pub role: Option<Role>,
+    #[ts(skip = true)]
+    pub password: String,
+}
+
+#[derive(TS, Clone)]
+#[ts(tag = "type", rename_all = "camelCase")]
+pub enum Role {
+    Admin,
+    Guest(u8),
+}
+"#,
+        );
+        write(
+            &root,
+            "src/models/auth.rs",
+            r#"
+#[derive(TS, Clone)]
+pub struct Token {
+    pub value: std::collections::HashMap<String, u64>,
+}
+"#,
+        );
+        gents_cli::run(&root.join("gents.toml"));
+
+        let read = |f: &str| fs::read_to_string(root.join("generated").join(f)).unwrap();
+        assert_eq!(
+            read("models/user.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { Token } from './auth'
+
+// A user.
+export interface User {
+    name: string
+    tokens: readonly Token[]
+    role?: Role
+}
+
+export type Role =
+    | 'admin'
+    | { type: 'guest'; value: number }
+"#
+        );
+        assert_eq!(
+            read("session.ts"),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { User } from './models/user'
+
+export interface Session<T> {
+    sessionId: string
+    user: User
+    data: readonly T[]
+}
+"#
+        );
+        assert!(read("models/auth.ts").contains("value: Map<string, number>"));
+        assert!(read("index.ts").contains("export * from './session'"));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_gen_groups() {
+        let root = std::env::temp_dir().join("gents_test_cli_groups");
+        let _ = fs::remove_dir_all(&root);
+        write(
+            &root,
+            "gents.toml",
+            r#"
+rename_all = "camelCase"
+
+[integers]
+u64 = "bigint"
+
+[imports]
+Dayjs = "dayjs"
+
+[[groups]]
+name = "frontend"
+out_dir = "web"
+tags = ["frontend"]
+
+[[groups]]
+name = "admin"
+out_dir = "admin"
+module_prefix = "my_crate::admin"
+"#,
+        );
+        write(
+            &root,
+            "src/lib.rs",
+            r#"
+pub mod admin;
+
+#[derive(TS, Clone)]
+#[ts(export = "frontend")]
+pub struct Order {
+    pub order_id: u64,
+    pub created_at: Dayjs,
+    pub item: Item,
+}
+
+#[derive(TS, Clone)]
+pub struct Item {
+    pub item_name: String,
+}
+
+#[derive(TS, Clone)]
+pub struct Unused {
+    pub f1: u8,
+}
+"#,
+        );
+        write(
+            &root,
+            "src/admin.rs",
+            r#"
+#[derive(TS, Clone)]
+pub struct Report {
+    pub total: u32,
+}
+"#,
+        );
+        gents_cli::run(&root.join("gents.toml"));
+
+        let files = |dir: &str| {
+            let mut files = fs::read_dir(root.join(dir))
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        assert_eq!(files("web"), vec!["item.ts", "order.ts"]);
+        assert_eq!(files("admin"), vec!["admin.ts"]);
+        assert_eq!(
+            fs::read_to_string(root.join("web/order.ts")).unwrap(),
+            r#"// DO NOT EDIT. CODE GENERATED BY gents.
+import { Item } from './item'
+import { Dayjs } from 'dayjs'
+
+export interface Order {
+    orderId: bigint
+    createdAt: Dayjs
+    item: Item
+}
+"#
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_build() {
+        let root = std::env::temp_dir().join("gents_test_build");
+        let _ = fs::remove_dir_all(&root);
+        write(
+            &root,
+            "gents.toml",
+            r#"
+[[groups]]
+name = "frontend"
+out_dir = "unused"
+"#,
+        );
+        write(&root, "src/lib.rs", "mod models;\n");
+        write(
+            &root,
+            "src/models.rs",
+            r#"
+#[derive(TS, Clone)]
+pub struct User {
+    pub name: String,
+    pub created_at: Instant,
+}
+"#,
+        );
+        let out_dir = root.join("out");
+        let config = gents::Config::from_file(&root.join("gents.toml"));
+        let mut build = gents::build::Build::from_config(config);
+        build.set_out_dir(out_dir.to_str().unwrap());
+        let output = build.run();
+
+        let user = out_dir.join("frontend/models.ts");
+        assert!(user.exists());
+        assert!(!root.join("unused").exists());
+        assert_eq!(output.generated_files, vec![user.clone()]);
+        assert_eq!(
+            output.sources,
+            vec![
+                root.join("gents.toml"),
+                root.join("src/lib.rs"),
+                root.join("src/models.rs")
+            ]
+        );
+        assert_eq!(
+            output.manifest(),
+            format!("pub const GENTS_FILES: &[&str] = &[\n    {:?},\n];\n", user)
+        );
+        assert_eq!(
+            output.warnings,
+            vec!["unknown type `Instant` in `crate::models`, generated as it is"]
         );
+        let _ = fs::remove_dir_all(&root);
     }
 }
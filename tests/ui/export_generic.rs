@@ -0,0 +1,9 @@
+use gents_derives::TS;
+
+#[derive(TS, Clone)]
+#[ts(file_name = "page.ts", export)]
+pub struct Page<T: gents::TS + Clone + 'static> {
+    pub items: Vec<T>,
+}
+
+fn main() {}
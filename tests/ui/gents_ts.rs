@@ -0,0 +1,15 @@
+// DO NOT EDIT. CODE GENERATED BY gents.
+#[::gents::wasm_bindgen::prelude::wasm_bindgen(
+    wasm_bindgen = ::gents::wasm_bindgen,
+    typescript_custom_section,
+)]
+const GENTS_TS: &'static str = r##"
+export interface User {
+    // Like "#admin", which does not close the raw string.
+    userName: string
+}
+
+export interface UserApi {
+    getUser(userName: string): User
+}
+"##;
@@ -0,0 +1,16 @@
+use gents_derives::ts_interface;
+use gents::wasm_bindgen::{self, prelude::wasm_bindgen};
+
+#[wasm_bindgen(wasm_bindgen = ::gents::wasm_bindgen)]
+pub struct Counter {
+    count: u32,
+}
+
+#[ts_interface(file_name = "counter.ts", wasm)]
+impl Counter {
+    pub async fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+fn main() {}
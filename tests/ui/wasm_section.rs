@@ -0,0 +1,3 @@
+mod gents_ts;
+
+fn main() {}